@@ -1,48 +1,125 @@
+mod filter;
+mod history;
+pub mod keymap;
 mod messages;
+mod migration;
+mod rich_text;
+mod search;
+mod selection;
+pub mod theme;
 mod widgets;
 
 pub use messages::try_message;
 use widgets::{
     StatusLine,
-    form::{Form, TagForm},
+    form::{Form, ProfileForm, TagForm},
 };
 
-use messages::{Message, MoveDirection};
+use std::{collections::HashSet, path::PathBuf, time::SystemTime};
+
+use filter::Filter;
+use history::{History, Snapshot};
+use keymap::{Key, Keymap};
+use messages::{Message, MoveDirection, ProfileEdit};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Flex, Layout, Rect},
-    style::{Color, Style, Stylize},
-    text::Line,
+    style::{Style, Stylize},
+    text::{Line, Span},
     widgets::{Block, Cell, List, ListState, Paragraph, Row, Table, TableState},
 };
+use search::Search;
+use selection::Selection;
 use serde::{Deserialize, Serialize};
+use theme::Theme;
+use tracing::{error, warn};
+use tui_input::backend::crossterm::EventHandler as _;
 
-use crate::mods::{OrderedItems, app_mod::Mod, tag::Tag};
+use crate::mods::{
+    Item, OrderedItems, Profile,
+    app_mod::Mod,
+    diff::{Change, LoadOrderDiff},
+    export::{self, Format},
+    game::{ModsConfigData, SaveMeta},
+    mod_list::{self, ListFormat},
+    scan::{self, ModSource, ScanError},
+    sorter::{self, Cycle},
+    tag::Tag,
+    validation::{self, Issue},
+};
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Mode {
     #[default]
     Normal,
+    Visual,
     CreateTag,
     ShowTags,
+    EditTag,
     Insert,
+    RemoveTag,
+    ShowCycles,
+    Issues,
+    ScanErrors,
+    Search,
+    Filter,
+    Profiles,
+    NameProfile,
+    Diff,
+    Saves,
+    Export,
+    Help,
 }
 impl Mode {
+    pub const ALL: [Mode; 18] = [
+        Mode::Normal,
+        Mode::Visual,
+        Mode::CreateTag,
+        Mode::ShowTags,
+        Mode::EditTag,
+        Mode::Insert,
+        Mode::RemoveTag,
+        Mode::ShowCycles,
+        Mode::Issues,
+        Mode::ScanErrors,
+        Mode::Search,
+        Mode::Filter,
+        Mode::Profiles,
+        Mode::NameProfile,
+        Mode::Diff,
+        Mode::Saves,
+        Mode::Export,
+        Mode::Help,
+    ];
+
     pub fn str_repr(&self) -> &'static str {
         match self {
             Mode::Normal => " NORMAL ",
+            Mode::Visual => " VISUAL ",
             Mode::CreateTag => " CREATE TAG ",
             Mode::ShowTags => " LISTING TAG ",
+            Mode::EditTag => " EDIT TAG ",
             Mode::Insert => " INSERT ",
+            Mode::RemoveTag => " REMOVE TAG ",
+            Mode::ShowCycles => " CYCLES ",
+            Mode::Issues => " ISSUES ",
+            Mode::ScanErrors => " SCAN ERRORS ",
+            Mode::Search => " SEARCH ",
+            Mode::Filter => " FILTER ",
+            Mode::Profiles => " PROFILES ",
+            Mode::NameProfile => " NAME PROFILE ",
+            Mode::Diff => " DIFF ",
+            Mode::Saves => " SAVES ",
+            Mode::Export => " EXPORT ",
+            Mode::Help => " HELP ",
         }
     }
-
-    fn color_repr(&self) -> Color {
+    /// Mode to return to when leaving this one.
+    pub fn parent(&self) -> Mode {
         match self {
-            Mode::Normal => Color::Rgb(0x45, 0x89, 0xff),
-            Mode::CreateTag => Color::Rgb(0x42, 0xbe, 0x65),
-            Mode::ShowTags => Color::Rgb(0xfe, 0x83, 0x2b),
-            Mode::Insert => Color::Rgb(0x42, 0xbe, 0x65),
+            Mode::EditTag => Mode::ShowTags,
+            Mode::NameProfile | Mode::Diff | Mode::Saves => Mode::Profiles,
+            _ => Mode::Normal,
         }
     }
 }
@@ -53,54 +130,291 @@ pub struct Model {
     movement_delta: String,
     table_state: TableState,
     list_state: ListState,
+    /// Tag of the selected mod highlighted in [`Mode::RemoveTag`].
+    mod_tag_cursor: usize,
+    issues_state: ListState,
+    popup_scroll: u16,
     status_line: StatusLine,
+    theme: Theme,
+    keymap: Keymap,
+    /// Start of a key sequence, see [`Keymap::resolve`].
+    pending_keys: Vec<Key>,
     tag_form: Form<TagForm>,
-    // keeping queries cached introduces a whole set of problems and
-    // it might not even be worth, TODO: benchmark
-    //mods_view: Vec<Mod>,
+    /// Name of the tag [`Mode::EditTag`] replaces.
+    edited_tag: Option<String>,
+    profiles_state: ListState,
+    profile_form: Form<ProfileForm>,
+    /// What [`Mode::NameProfile`] does with the name, and to which profile.
+    profile_edit: Option<(ProfileEdit, Option<String>)>,
+    /// Profile the highlighted one is compared with, the load order if `None`.
+    diff_base: Option<String>,
+    /// Titles of both sides of `diff`.
+    diff_titles: (String, String),
+    diff: LoadOrderDiff,
+    /// Folder listed by [`Mode::Saves`].
+    saves_dir: PathBuf,
+    /// Saves newest first, `None` if their mod list couldn't be read.
+    saves: Vec<(PathBuf, Option<SaveMeta>)>,
+    saves_state: ListState,
+    export_state: ListState,
+    /// Indices of the mods shown in the table, see [`Self::reselect_mod`].
+    rows: Vec<usize>,
     persistent: Persistent,
+    mods_config: ModsConfigData,
+    /// Whether `ModsConfig.xml` was read, it's only written back then.
+    writes_mods_config: bool,
+    cycles: Vec<Cycle>,
+    issues: Vec<Issue>,
+    sources: Vec<ModSource>,
+    scan_errors: Vec<ScanError>,
+    search: Search,
+    filter: Filter,
+    selection: Selection,
+    history: History,
+    show_details: bool,
+    details_scroll: u16,
+    /// Mod the details were scrolled for, they go back to the top when the
+    /// selection changes.
+    details_for: Option<String>,
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Persistent {
     pub mods: OrderedItems<Mod>,
     pub tags: OrderedItems<Tag>,
+    #[serde(default)]
+    pub profiles: OrderedItems<Profile>,
+    /// Profile the load order is saved to.
+    #[serde(default)]
+    pub current_profile: Option<String>,
 }
 impl Default for Persistent {
     fn default() -> Self {
         Self {
             mods: vec![].into(),
             tags: Default::default(),
+            profiles: Default::default(),
+            current_profile: None,
         }
     }
 }
+impl Persistent {
+    /// Reads a saved `mod_info.ron`, older layouts are migrated.
+    pub fn from_ron(buf: &str) -> Result<Self, ron::error::SpannedError> {
+        let mut res: Self = match ron::de::from_str(buf) {
+            Ok(res) => res,
+            Err(e) => match ron::de::from_str::<migration::LegacyPersistent>(buf) {
+                Ok(legacy) => legacy.into(),
+                Err(_) => return Err(e),
+            },
+        };
+        res.mods.sync_tags(&res.tags);
+        Ok(res)
+    }
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+}
 
 impl Model {
-    pub fn new(persistent: Persistent) -> Self {
+    /// `mods_config` is `None` when there is no `ModsConfig.xml`, the load
+    /// order starts empty.
+    pub fn new(
+        persistent: Persistent,
+        mods_config: Option<ModsConfigData>,
+        sources: Vec<ModSource>,
+        saves_dir: PathBuf,
+        keymap: Keymap,
+        theme: Theme,
+    ) -> Self {
         let mut res = Self {
             persistent,
+            writes_mods_config: mods_config.is_some(),
+            mods_config: mods_config.unwrap_or_default(),
+            sources,
+            saves_dir,
+            keymap,
+            theme,
+            show_details: true,
             ..Default::default()
         };
 
-        if !res.persistent.mods.is_empty() {
-            res.table_state.select_first();
-        }
+        res.reselect_mod(None);
         if !res.persistent.tags.is_empty() {
             res.list_state.select_first();
         }
-        res.status_line.state.background_color = Color::Rgb(0x0b, 0x0b, 0x0b);
+        res.status_line.state.theme = res.theme.clone();
         res.status_line.state.change_mode(res.current_mode);
         res.status_line
             .state
-            .change_hint(res.current_mode, &res.movement_delta);
+            .change_hint(res.current_mode, &res.movement_delta, &res.keymap);
 
-        res.tag_form.state.background_color = Color::Rgb(0x26, 0x26, 0x26);
+        res.tag_form.state.background_color = res.theme.popup;
+        res.tag_form.state.text_color = res.theme.text;
+        res.profile_form.state.background_color = res.theme.popup;
+        res.profile_form.state.text_color = res.theme.text;
+        res.show_filter();
         res
     }
     pub fn should_close(&self) -> bool {
         self.should_close
     }
-    pub fn result(self) -> Persistent {
+    pub fn result(mut self) -> (Persistent, ModsConfigData) {
+        self.store_profile();
+        (self.persistent, self.mods_config)
+    }
+    /// Saves the load order into the current profile.
+    fn store_profile(&mut self) {
+        if let Some(name) = &self.persistent.current_profile {
+            self.persistent.profiles.upsert(Profile {
+                name: name.clone(),
+                active_mods: self.mods_config.active_mods.clone(),
+            });
+        }
+    }
+    fn highlighted_profile(&self) -> Option<&Profile> {
         self.persistent
+            .profiles
+            .get(self.profiles_state.selected()?)
+    }
+    /// Lists the saves of `saves_dir` and reads their mod lists.
+    fn read_saves(&mut self) {
+        let entries = match std::fs::read_dir(&self.saves_dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Couldn't list {}: {e}", self.saves_dir.display());
+                self.saves.clear();
+                return;
+            }
+        };
+        let mut saves: Vec<(SystemTime, PathBuf)> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rws"))
+            .map(|path| {
+                let modified = path.metadata().and_then(|m| m.modified());
+                (modified.unwrap_or(SystemTime::UNIX_EPOCH), path)
+            })
+            .collect();
+        saves.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        self.saves = saves
+            .into_iter()
+            .map(|(_, path)| {
+                let meta = SaveMeta::open(&path).unwrap_or_else(|e| {
+                    warn!("Couldn't read {}: {e}", path.display());
+                    None
+                });
+                (path, meta)
+            })
+            .collect();
+    }
+    /// Shows `text` in the status line.
+    pub fn notify(&mut self, text: impl Into<String>) {
+        self.status_line.state.notify(text);
+    }
+    /// Hides the notification, called before handling a key.
+    pub fn dismiss_notification(&mut self) {
+        self.status_line.state.dismiss();
+    }
+    /// Merges the mods on disk into [`Persistent::mods`], the outcome is
+    /// shown in the status line.
+    pub fn rescan(&mut self) {
+        let report = scan::scan_sources(&self.sources);
+        for e in &report.errors {
+            error!("Couldn't load {}: {}", e.path.display(), e.kind);
+        }
+        let selected = self.selected_mod_id();
+        let summary = self.persistent.mods.merge_scanned(report.mods);
+        self.reselect_mod(selected);
+        self.scan_errors = report.errors;
+        let text = if self.scan_errors.is_empty() {
+            format!("Rescanned: {summary}")
+        } else {
+            format!(
+                "Rescanned: {summary}, {} errors ('E' to show)",
+                self.scan_errors.len()
+            )
+        };
+        self.status_line.state.notify(text);
+    }
+
+    /// Mods shown in the table, in order.
+    fn shown_mods(&self) -> impl Iterator<Item = &Mod> + Clone {
+        shown_mods(&self.rows, &self.persistent.mods)
+    }
+    fn selected_mod(&self) -> Option<&Mod> {
+        let row = self.table_state.selected()?;
+        self.persistent.mods.get(*self.rows.get(row)?)
+    }
+    /// Mods picked in [`Mode::Visual`], or the one under the cursor.
+    fn target_mod_ids(&self) -> HashSet<String> {
+        let res = self
+            .selection
+            .package_ids(self.shown_mods(), self.table_state.selected());
+        if !res.is_empty() {
+            return res;
+        }
+        self.selected_mod_id().into_iter().collect()
+    }
+    fn selected_mod_id(&self) -> Option<String> {
+        Some(self.selected_mod()?.metadata.package_id.clone())
+    }
+    /// Recomputes the rows after the table was re-sorted or filtered,
+    /// keeping the mod `package_id` selected if it is still shown.
+    fn reselect_mod(&mut self, package_id: Option<String>) {
+        self.rows = self
+            .persistent
+            .mods
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| self.filter.matches(m, &self.mods_config))
+            .map(|(idx, _)| idx)
+            .collect();
+        let row =
+            package_id.and_then(|id| self.shown_mods().position(|m| m.metadata.package_id == id));
+        if row.is_some() {
+            self.table_state.select(row);
+        } else if self.rows.is_empty() {
+            self.table_state.select(None);
+        } else if self
+            .table_state
+            .selected()
+            .is_none_or(|row| row >= self.rows.len())
+        {
+            self.table_state.select_first();
+        }
+        // matches are row indices, they move with the rows
+        if !self.search.query().is_empty() {
+            self.search
+                .update_matches(shown_mods(&self.rows, &self.persistent.mods));
+        }
+    }
+    /// Shows the active filter and profile in the status line.
+    fn show_filter(&mut self) {
+        self.status_line.state.filter = self
+            .filter
+            .active
+            .as_ref()
+            .map(|q| q.text().to_owned())
+            .unwrap_or_default();
+        self.status_line.state.profile = self.persistent.current_profile.clone();
+        self.status_line.state.change_mode(self.current_mode);
+    }
+
+    /// Keeps the selected tag in bounds after tags were removed.
+    fn clamp_tag_list(&mut self) {
+        if self.persistent.tags.is_empty() {
+            self.list_state.select(None);
+        } else if self.list_state.selected() >= Some(self.persistent.tags.len()) {
+            self.list_state.select_last();
+        }
+    }
+    /// Keeps the highlighted profile in bounds after profiles were removed.
+    fn clamp_profile_list(&mut self) {
+        if self.persistent.profiles.is_empty() {
+            self.profiles_state.select(None);
+        } else if self.profiles_state.selected() >= Some(self.persistent.profiles.len()) {
+            self.profiles_state.select_last();
+        }
     }
 
     pub fn view(&mut self, f: &mut Frame) {
@@ -110,35 +424,70 @@ impl Model {
             .constraints(vec![Constraint::Min(1), Constraint::Max(1)])
             .split(area);
 
-        let table_color = Color::Rgb(0x16, 0x16, 0x16);
+        let table_color = self.theme.background;
+        let picked = self
+            .selection
+            .package_ids(self.shown_mods(), self.table_state.selected());
         let mut rows = vec![];
-        // TODO: Keep in sync when persistent mods is updated
-        for (idx, game_mod) in self.persistent.mods.iter().enumerate() {
+        for (idx, game_mod) in self.shown_mods().enumerate() {
             let line_num = match self.table_state.selected() {
                 Some(s) => {
                     if s == idx {
                         format!("{:<3}", idx)
                     } else {
-                        let n = if idx < s { s - idx } else { idx - s };
-                        format!("{:>3}", n)
+                        format!("{:>3}", s.abs_diff(idx))
                     }
                 }
                 None => format!("{:>3}", idx),
             };
             let selected = self.table_state.selected().is_some_and(|v| v == idx);
-            let mut name = Cell::from(game_mod.metadata.name.to_owned());
+            let mut name = if self.search.matches().contains(&idx) {
+                Cell::from(
+                    self.search
+                        .highlight(&game_mod.metadata.name, self.theme.mode(Mode::Search)),
+                )
+            } else {
+                Cell::from(game_mod.metadata.name.to_owned())
+            };
+            if game_mod.missing {
+                name = name.fg(self.theme.muted).crossed_out();
+            }
             if selected {
-                name = name.bg(Color::Rgb(0x39, 0x39, 0x39));
+                name = name.bg(self.theme.selection);
+            } else if picked.contains(&game_mod.metadata.package_id) {
+                name = name.bg(self.theme.marked);
             }
+            let load_order = match self.mods_config.position(&game_mod.metadata.package_id) {
+                Some(pos) => Cell::from(format!("{:>4}", pos)),
+                None => Cell::from(""),
+            };
             rows.push(Row::new(vec![
                 Cell::from(line_num),
+                load_order,
+                Cell::from(game_mod.source.str_repr()),
                 name,
-                Cell::from(game_mod.tags_styled_line(table_color, selected)),
+                if selected && matches!(self.current_mode, Mode::RemoveTag) {
+                    Cell::from(game_mod.tags_line_highlighting(
+                        &self.persistent.tags,
+                        table_color,
+                        self.theme.muted,
+                        self.mod_tag_cursor,
+                    ))
+                } else {
+                    Cell::from(game_mod.tags_styled_line(
+                        &self.persistent.tags,
+                        table_color,
+                        self.theme.muted,
+                        selected,
+                    ))
+                },
             ]));
         }
 
         let widths = [
             Constraint::Length(3),
+            Constraint::Length(4),
+            Constraint::Length(5),
             Constraint::Fill(1),
             Constraint::Percentage(60),
         ];
@@ -146,36 +495,248 @@ impl Model {
             .block(Block::new().title("Table"))
             .row_highlight_style(Style::new().bold())
             .highlight_symbol(">>")
-            .bg(table_color);
+            .bg(table_color)
+            .fg(self.theme.text);
 
-        f.render_stateful_widget(table, main_layout[0], &mut self.table_state);
+        let table_area = if self.show_details {
+            let [table_area, details_area] =
+                Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .areas(main_layout[0]);
+            self.draw_details(f, details_area);
+            table_area
+        } else {
+            main_layout[0]
+        };
+        f.render_stateful_widget(table, table_area, &mut self.table_state);
+        if matches!(self.current_mode, Mode::Search) {
+            self.status_line
+                .state
+                .prompt(&format!("/{} ", self.search.query()));
+            let status = main_layout[1];
+            f.set_cursor_position((status.x + status.width.saturating_sub(1), status.y));
+        }
+        if matches!(self.current_mode, Mode::Filter) {
+            self.status_line
+                .state
+                .prompt(&format!("filter: {} ", self.filter.input.value()));
+            let status = main_layout[1];
+            f.set_cursor_position((status.x + status.width.saturating_sub(1), status.y));
+        }
         self.status_line.render_widget(f, main_layout[1]);
         self.draw_popup(f, area);
     }
 
+    /// Everything known about the selected mod.
+    fn draw_details(&mut self, f: &mut Frame, area: Rect) {
+        let package_id = self.selected_mod_id();
+        if package_id != self.details_for {
+            self.details_for = package_id;
+            self.details_scroll = 0;
+        }
+        let block = Block::new()
+            .title("Details")
+            .borders(ratatui::widgets::Borders::LEFT)
+            .border_style(Style::default().fg(self.theme.muted));
+        let Some(game_mod) = self.selected_mod() else {
+            let p = Paragraph::new(Line::from("No mod selected").italic())
+                .block(block)
+                .bg(self.theme.background)
+                .fg(self.theme.text);
+            f.render_widget(p, area);
+            return;
+        };
+        let metadata = &game_mod.metadata;
+        let version = self.mods_config.game_version();
+        let heading = |text: &str| Line::styled(text.to_owned(), Style::default().bold());
+        let field = |name: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{name}: "), Style::default().fg(self.theme.muted)),
+                Span::raw(value),
+            ])
+        };
+        let mut lines = vec![
+            Line::styled(metadata.name.clone(), Style::default().bold()),
+            field("packageId", metadata.package_id.clone()),
+        ];
+        let authors: Vec<&str> = metadata.author_names().collect();
+        if !authors.is_empty() {
+            lines.push(field("Authors", authors.join(", ")));
+        }
+        if let Some(mod_version) = &metadata.mod_version {
+            lines.push(field("Version", mod_version.clone()));
+        }
+        lines.push(field(
+            "Source",
+            format!("{} {}", game_mod.source.str_repr(), game_mod.path.display()),
+        ));
+        if let Some(url) = &metadata.url {
+            lines.push(field("Url", url.clone()));
+        }
+        let supported = &metadata.supported_versions;
+        let supports_game = version.is_none_or(|v| supported.iter().any(|s| s == v));
+        lines.push(Line::from(vec![
+            Span::styled("Supports: ", Style::default().fg(self.theme.muted)),
+            Span::styled(
+                supported.join(", "),
+                if supports_game {
+                    Style::default()
+                } else {
+                    Style::default().fg(self.theme.mode(Mode::Issues))
+                },
+            ),
+        ]));
+
+        let dependencies = metadata.dependencies_for(version);
+        if !dependencies.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(heading("Dependencies"));
+            for dep in dependencies {
+                let installed = self
+                    .persistent
+                    .mods
+                    .iter()
+                    .any(|m| m.identifier().eq_ignore_ascii_case(&dep.package_id));
+                let (status, color) = if self.mods_config.is_active(&dep.package_id) {
+                    ("", self.theme.text)
+                } else if installed {
+                    (" (inactive)", self.theme.mode(Mode::Issues))
+                } else {
+                    (" (not installed)", self.theme.mode(Mode::ShowCycles))
+                };
+                lines.push(Line::styled(
+                    format!("  {} ({}){status}", dep.display_name, dep.package_id),
+                    Style::default().fg(color),
+                ));
+            }
+        }
+        let lists = [
+            ("Load after", metadata.load_after_for(version)),
+            ("Load before", metadata.load_before_for(version)),
+            (
+                "Incompatible with",
+                metadata.incompatible_with.iter().collect(),
+            ),
+        ];
+        for (name, list) in lists {
+            if list.is_empty() {
+                continue;
+            }
+            lines.push(Line::from(""));
+            lines.push(heading(name));
+            lines.extend(list.into_iter().map(|id| Line::from(format!("  {id}"))));
+        }
+
+        lines.push(Line::from(""));
+        lines.extend(rich_text::to_lines(
+            metadata.description_for(version),
+            Style::default(),
+        ));
+        let p = Paragraph::new(lines)
+            .block(block)
+            .bg(self.theme.background)
+            .fg(self.theme.text)
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .scroll((self.details_scroll, 0));
+        f.render_widget(p, area);
+    }
+
     #[inline]
     fn draw_popup(&mut self, f: &mut Frame, area: Rect) {
         match self.current_mode {
             // TODO: This is horrid, fix
-            Mode::CreateTag => {
+            Mode::CreateTag | Mode::EditTag => {
                 let area =
                     Self::popup_area(area, Constraint::Percentage(30), Constraint::Max(3 * 3));
                 self.tag_form.render_widget(f, area);
             }
+            Mode::NameProfile => {
+                let area = Self::popup_area(area, Constraint::Percentage(30), Constraint::Max(3));
+                self.profile_form.render_widget(f, area);
+            }
+            Mode::Profiles => {
+                let area =
+                    Self::popup_area(area, Constraint::Percentage(50), Constraint::Percentage(50));
+                f.render_widget(ratatui::widgets::Clear, area);
+                let current = self.persistent.current_profile.as_deref();
+                let mut items: Vec<Line> = self
+                    .persistent
+                    .profiles
+                    .iter()
+                    .map(|profile| {
+                        let marker = match (
+                            current == Some(profile.name.as_str()),
+                            self.diff_base.as_ref() == Some(&profile.name),
+                        ) {
+                            (true, true) => "*=",
+                            (true, false) => "* ",
+                            (false, true) => " =",
+                            (false, false) => "  ",
+                        };
+                        Line::from(vec![
+                            Span::raw(format!("{marker}{}", profile.name)),
+                            Span::styled(
+                                format!("  {} mods", profile.active_mods.len()),
+                                Style::default().fg(self.theme.muted),
+                            ),
+                        ])
+                    })
+                    .collect();
+                if items.is_empty() {
+                    items.push(Line::from("No profiles, 'n' saves the load order as one").italic());
+                }
+                let list = List::new(items)
+                    .block(Block::bordered().title("Profiles"))
+                    .bg(self.theme.popup)
+                    .fg(self.theme.text)
+                    .highlight_style(Style::new().bold().fg(self.theme.mode(Mode::Profiles)))
+                    .highlight_symbol(">>");
+                f.render_stateful_widget(list, area, &mut self.profiles_state);
+            }
             Mode::ShowTags => {
                 let area =
                     Self::popup_area(area, Constraint::Percentage(70), Constraint::Percentage(60));
-                let bg_color = Color::Rgb(0x26, 0x26, 0x26);
-                let p = Paragraph::new(vec![self.persistent.tags.styled_line(bg_color, false)])
-                    .block(Block::bordered().title("Tags"))
-                    .bg(bg_color);
+                let bg_color = self.theme.popup;
                 f.render_widget(ratatui::widgets::Clear, area);
-                f.render_widget(p, area);
+
+                let selected = match self.list_state.selected() {
+                    Some(i) => crate::mods::tag::SelectedTag::Index(i),
+                    None => crate::mods::tag::SelectedTag::None,
+                };
+                let spans = self
+                    .persistent
+                    .tags
+                    .spans(bg_color, self.theme.muted, selected);
+                let mut items = vec![];
+                for (span, tag) in spans.zip(self.persistent.tags.iter()) {
+                    let carried_by = self
+                        .persistent
+                        .mods
+                        .iter()
+                        .filter(|m| m.has_tag(&tag.name))
+                        .count();
+                    items.push(Line::from(vec![
+                        span,
+                        Span::styled(
+                            format!("  score {}, {carried_by} mods", tag.score),
+                            Style::default().fg(self.theme.muted),
+                        ),
+                    ]));
+                }
+                if items.is_empty() {
+                    items.push(Line::from("No tags created").italic());
+                }
+                let list = List::new(items)
+                    .block(Block::bordered().title("Tags"))
+                    .bg(bg_color)
+                    .fg(self.theme.text)
+                    .highlight_symbol(">>");
+
+                f.render_stateful_widget(list, area, &mut self.list_state);
             }
             Mode::Insert => {
                 let area =
                     Self::popup_area(area, Constraint::Percentage(70), Constraint::Percentage(60));
-                let bg_color = Color::Rgb(0x26, 0x26, 0x26);
+                let bg_color = self.theme.popup;
                 f.render_widget(ratatui::widgets::Clear, area);
 
                 let mut items = vec![];
@@ -183,20 +744,263 @@ impl Model {
                     Some(i) => crate::mods::tag::SelectedTag::Index(i),
                     None => crate::mods::tag::SelectedTag::None,
                 };
-                for span in self.persistent.tags.spans(bg_color, selected) {
+                for span in self
+                    .persistent
+                    .tags
+                    .spans(bg_color, self.theme.muted, selected)
+                {
                     items.push(Line::from(vec![span]));
                 }
                 let list = List::new(items)
                     .block(Block::bordered().title("Tags"))
                     .bg(bg_color)
+                    .fg(self.theme.text)
                     .highlight_symbol(">>")
                     .repeat_highlight_symbol(true);
 
                 f.render_stateful_widget(list, area, &mut self.list_state);
             }
+            Mode::ShowCycles => {
+                let area =
+                    Self::popup_area(area, Constraint::Percentage(70), Constraint::Percentage(60));
+                let bg_color = self.theme.popup;
+                let mut lines = vec![];
+                for (idx, cycle) in self.cycles.iter().enumerate() {
+                    lines.push(Line::styled(
+                        format!("Cycle {}: {}", idx + 1, cycle.package_ids.join(", ")),
+                        Style::default()
+                            .bold()
+                            .fg(self.theme.mode(Mode::ShowCycles)),
+                    ));
+                    for rule in &cycle.rules {
+                        lines.push(Line::from(format!("  {rule}")));
+                    }
+                }
+                if lines.is_empty() {
+                    lines.push(Line::from("No cycles found").italic());
+                }
+                self.clamp_popup_scroll(lines.len(), area);
+                let p = Paragraph::new(lines)
+                    .block(Block::bordered().title("Load order cycles"))
+                    .bg(bg_color)
+                    .fg(self.theme.text)
+                    .scroll((self.popup_scroll, 0));
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(p, area);
+            }
+            Mode::Help => {
+                let area =
+                    Self::popup_area(area, Constraint::Percentage(70), Constraint::Percentage(80));
+                let bg_color = self.theme.popup;
+                let mut lines = vec![];
+                for mode in Mode::ALL {
+                    if !lines.is_empty() {
+                        lines.push(Line::from(""));
+                    }
+                    lines.push(Line::styled(
+                        mode.str_repr(),
+                        Style::default()
+                            .bold()
+                            .bg(self.theme.mode(mode))
+                            .fg(bg_color),
+                    ));
+                    for (keys, action) in self.keymap.help(mode) {
+                        lines.push(Line::from(vec![
+                            Span::styled(
+                                format!("  {keys:<20}"),
+                                Style::default().bold().fg(self.theme.mode(mode)),
+                            ),
+                            Span::raw(action),
+                        ]));
+                    }
+                }
+                self.clamp_popup_scroll(lines.len(), area);
+                let p = Paragraph::new(lines)
+                    .block(Block::bordered().title("Help"))
+                    .bg(bg_color)
+                    .fg(self.theme.text)
+                    .scroll((self.popup_scroll, 0));
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(p, area);
+            }
+            Mode::ScanErrors => {
+                let area =
+                    Self::popup_area(area, Constraint::Percentage(70), Constraint::Percentage(60));
+                let bg_color = self.theme.popup;
+                let mut lines = vec![];
+                for e in &self.scan_errors {
+                    lines.push(Line::styled(
+                        e.path.display().to_string(),
+                        Style::default()
+                            .bold()
+                            .fg(self.theme.mode(Mode::ScanErrors)),
+                    ));
+                    lines.push(Line::from(format!("  {}", e.kind)));
+                }
+                if lines.is_empty() {
+                    lines.push(Line::from("Every mod was loaded").italic());
+                }
+                self.clamp_popup_scroll(lines.len(), area);
+                let p = Paragraph::new(lines)
+                    .block(Block::bordered().title("Scan errors"))
+                    .bg(bg_color)
+                    .fg(self.theme.text)
+                    .scroll((self.popup_scroll, 0));
+                f.render_widget(ratatui::widgets::Clear, area);
+                f.render_widget(p, area);
+            }
+            Mode::Diff => {
+                let area =
+                    Self::popup_area(area, Constraint::Percentage(80), Constraint::Percentage(80));
+                f.render_widget(ratatui::widgets::Clear, area);
+                let [left, right] = Layout::horizontal([Constraint::Fill(1); 2]).areas(area);
+                let (added, removed, moved) = self.diff.counts();
+                self.clamp_popup_scroll(self.diff.left.len().max(self.diff.right.len()), area);
+                let sides = [
+                    (left, &self.diff.left, &self.diff_titles.0, String::new()),
+                    (
+                        right,
+                        &self.diff.right,
+                        &self.diff_titles.1,
+                        format!("{added} added, {removed} removed, {moved} moved"),
+                    ),
+                ];
+                for (area, side, title, summary) in sides {
+                    let lines: Vec<Line> = side
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, (id, change))| {
+                            let color = match change {
+                                Change::Kept => self.theme.text,
+                                Change::Added => self.theme.mode(Mode::Insert),
+                                Change::Removed => self.theme.mode(Mode::RemoveTag),
+                                Change::Moved => self.theme.mode(Mode::Issues),
+                            };
+                            Line::styled(
+                                format!("{} {:>4} {id}", change.marker(), idx + 1),
+                                Style::default().fg(color),
+                            )
+                        })
+                        .collect();
+                    let p = Paragraph::new(lines)
+                        .block(
+                            Block::bordered()
+                                .title(title.as_str())
+                                .title_bottom(Line::from(summary).right_aligned()),
+                        )
+                        .bg(self.theme.popup)
+                        .fg(self.theme.text)
+                        .scroll((self.popup_scroll, 0));
+                    f.render_widget(p, area);
+                }
+            }
+            Mode::Export => {
+                let area = Self::popup_area(area, Constraint::Percentage(40), Constraint::Max(8));
+                f.render_widget(ratatui::widgets::Clear, area);
+                let items: Vec<Line> = export_targets()
+                    .map(|(name, path)| {
+                        Line::from(vec![
+                            Span::raw(name),
+                            Span::styled(
+                                format!("  {path}"),
+                                Style::default().fg(self.theme.muted),
+                            ),
+                        ])
+                    })
+                    .collect();
+                let list = List::new(items)
+                    .block(Block::bordered().title("Export"))
+                    .bg(self.theme.popup)
+                    .fg(self.theme.text)
+                    .highlight_style(Style::new().bold().fg(self.theme.mode(Mode::Export)))
+                    .highlight_symbol(">>");
+                f.render_stateful_widget(list, area, &mut self.export_state);
+            }
+            Mode::Saves => {
+                let area =
+                    Self::popup_area(area, Constraint::Percentage(70), Constraint::Percentage(60));
+                f.render_widget(ratatui::widgets::Clear, area);
+                let mut items: Vec<Line> = self
+                    .saves
+                    .iter()
+                    .map(|(path, meta)| {
+                        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                        let Some(meta) = meta else {
+                            return Line::from(vec![
+                                Span::raw(name.into_owned()),
+                                Span::styled(
+                                    "  no mod list",
+                                    Style::default().fg(self.theme.muted),
+                                ),
+                            ]);
+                        };
+                        let missing = meta.missing(self.persistent.mods.iter()).len();
+                        let missing_color = if missing == 0 {
+                            self.theme.muted
+                        } else {
+                            self.theme.mode(Mode::RemoveTag)
+                        };
+                        Line::from(vec![
+                            Span::raw(name.into_owned()),
+                            Span::styled(
+                                format!("  {}, {} mods", meta.game_version, meta.mods.len()),
+                                Style::default().fg(self.theme.muted),
+                            ),
+                            Span::styled(
+                                format!(", {missing} not installed"),
+                                Style::default().fg(missing_color),
+                            ),
+                        ])
+                    })
+                    .collect();
+                if items.is_empty() {
+                    items.push(
+                        Line::from(format!("No saves in {}", self.saves_dir.display())).italic(),
+                    );
+                }
+                let list = List::new(items)
+                    .block(Block::bordered().title("Saves"))
+                    .bg(self.theme.popup)
+                    .fg(self.theme.text)
+                    .highlight_style(Style::new().bold().fg(self.theme.mode(Mode::Saves)))
+                    .highlight_symbol(">>");
+                f.render_stateful_widget(list, area, &mut self.saves_state);
+            }
+            Mode::Issues => {
+                let area =
+                    Self::popup_area(area, Constraint::Percentage(70), Constraint::Percentage(60));
+                let bg_color = self.theme.popup;
+                f.render_widget(ratatui::widgets::Clear, area);
+
+                let mut items: Vec<Line> = self
+                    .issues
+                    .iter()
+                    .map(|issue| Line::from(issue.to_string()))
+                    .collect();
+                if items.is_empty() {
+                    items.push(Line::from("No issues found").italic());
+                }
+                let list = List::new(items)
+                    .block(Block::bordered().title("Issues"))
+                    .bg(bg_color)
+                    .fg(self.theme.text)
+                    .highlight_style(Style::new().bold().fg(self.theme.mode(Mode::Issues)))
+                    .highlight_symbol(">>");
+
+                f.render_stateful_widget(list, area, &mut self.issues_state);
+            }
             _ => {}
         }
     }
+    /// Keeps the last of the `lines` of a bordered popup in `area` from
+    /// scrolling past its bottom.
+    fn clamp_popup_scroll(&mut self, lines: usize, area: Rect) {
+        let height = area.height.saturating_sub(2);
+        let max = u16::try_from(lines)
+            .unwrap_or(u16::MAX)
+            .saturating_sub(height);
+        self.popup_scroll = self.popup_scroll.min(max);
+    }
     fn popup_area(area: Rect, constraint_x: Constraint, constraint_y: Constraint) -> Rect {
         let vertical = Layout::vertical([constraint_y]).flex(Flex::Center);
         let horizontal = Layout::horizontal([constraint_x]).flex(Flex::Center);
@@ -206,76 +1010,640 @@ impl Model {
     }
 
     pub fn update(&mut self, msg: Message) -> Option<Message> {
+        let before = msg
+            .is_undoable()
+            .then(|| Snapshot::new(&self.persistent, &self.mods_config));
+        let res = self.handle(msg);
+        // moving, toggling or editing may change what the filter shows
+        let selected = self.selected_mod_id();
+        self.reselect_mod(selected);
+        if let Some(before) = before
+            && self
+                .history
+                .record(before, &self.persistent, &self.mods_config)
+        {
+            self.status_line.state.history = self.history.counts();
+            self.status_line.state.change_hint(
+                self.current_mode,
+                &self.movement_delta,
+                &self.keymap,
+            );
+        }
+        res
+    }
+    fn handle(&mut self, msg: Message) -> Option<Message> {
+        if !matches!(msg, Message::PendingKeys(_)) && !self.pending_keys.is_empty() {
+            self.pending_keys.clear();
+            self.status_line.state.change_hint(
+                self.current_mode,
+                &self.movement_delta,
+                &self.keymap,
+            );
+        }
         match msg {
-            Message::ClearCommand => self.movement_delta.clear(),
+            Message::ClearCommand => {
+                if !self.movement_delta.is_empty() {
+                    self.movement_delta.clear();
+                    self.status_line.state.change_hint(
+                        self.current_mode,
+                        &self.movement_delta,
+                        &self.keymap,
+                    );
+                }
+            }
             Message::AppendMovement(ch) => {
                 self.movement_delta.push(ch);
-                self.status_line
-                    .state
-                    .change_hint(self.current_mode, &self.movement_delta);
+                self.status_line.state.change_hint(
+                    self.current_mode,
+                    &self.movement_delta,
+                    &self.keymap,
+                );
+            }
+            Message::PendingKeys(keys) => {
+                self.pending_keys = keys;
+                let typed: String = self.pending_keys.iter().map(Key::to_string).collect();
+                self.status_line.state.change_hint(
+                    self.current_mode,
+                    &(self.movement_delta.clone() + &typed),
+                    &self.keymap,
+                );
             }
             Message::MoveDirection(direction) => {
-                let d: usize = self.movement_delta.parse().unwrap_or(1);
-                // TODO: This is horrid, refactor to use proper patterns
+                let count: Option<usize> = self.movement_delta.parse().ok();
                 match self.current_mode {
-                    Mode::Normal => {
-                        let new = self.table_state.selected().map(|s| match direction {
-                            MoveDirection::Up => s.saturating_sub(d),
-                            MoveDirection::Down => s
-                                .saturating_add(d)
-                                .min(self.persistent.mods.len().saturating_sub(1)),
-                            MoveDirection::Left => s.saturating_sub(d),
-                            MoveDirection::Right => s
-                                .saturating_add(d)
-                                .min(self.persistent.mods.len().saturating_sub(1)),
-                        });
+                    Mode::Normal | Mode::Visual => {
+                        let len = self.rows.len();
+                        let new = self
+                            .table_state
+                            .selected()
+                            .map(|s| direction.apply(s, count, len));
                         self.table_state.select(new);
                     }
-                    Mode::Insert => {
-                        let new = self.list_state.selected().map(|s| match direction {
-                            MoveDirection::Up => s.saturating_sub(d),
-                            MoveDirection::Down => s
-                                .saturating_add(d)
-                                .min(self.persistent.tags.len().saturating_sub(1)),
-                            MoveDirection::Left => s.saturating_sub(d),
-                            MoveDirection::Right => s
-                                .saturating_add(d)
-                                .min(self.persistent.tags.len().saturating_sub(1)),
-                        });
+                    Mode::RemoveTag => {
+                        let len = self.selected_mod().map_or(0, |m| m.tags().len());
+                        self.mod_tag_cursor = direction.apply(self.mod_tag_cursor, count, len);
+                    }
+                    Mode::Insert | Mode::ShowTags => {
+                        let len = self.persistent.tags.len();
+                        let new = self
+                            .list_state
+                            .selected()
+                            .map(|s| direction.apply(s, count, len));
                         self.list_state.select(new);
                     }
+                    Mode::Profiles => {
+                        let len = self.persistent.profiles.len();
+                        let new = self
+                            .profiles_state
+                            .selected()
+                            .map(|s| direction.apply(s, count, len));
+                        self.profiles_state.select(new);
+                    }
+                    Mode::Export => {
+                        let len = export_targets().count();
+                        let new = self
+                            .export_state
+                            .selected()
+                            .map(|s| direction.apply(s, count, len));
+                        self.export_state.select(new);
+                    }
+                    Mode::Saves => {
+                        let len = self.saves.len();
+                        let new = self
+                            .saves_state
+                            .selected()
+                            .map(|s| direction.apply(s, count, len));
+                        self.saves_state.select(new);
+                    }
+                    Mode::Issues => {
+                        let len = self.issues.len();
+                        let new = self
+                            .issues_state
+                            .selected()
+                            .map(|s| direction.apply(s, count, len));
+                        self.issues_state.select(new);
+                    }
+                    Mode::ShowCycles | Mode::ScanErrors | Mode::Help | Mode::Diff => {
+                        let d = u16::try_from(count.unwrap_or(1)).unwrap_or(u16::MAX);
+                        self.popup_scroll = match direction {
+                            MoveDirection::Up | MoveDirection::Left => {
+                                self.popup_scroll.saturating_sub(d)
+                            }
+                            MoveDirection::Down | MoveDirection::Right => {
+                                self.popup_scroll.saturating_add(d)
+                            }
+                            MoveDirection::Top => 0,
+                            // clamped to the content when drawn
+                            MoveDirection::Bottom => u16::MAX,
+                        };
+                    }
                     _ => {}
                 }
                 return Some(Message::ClearCommand);
             }
-            Message::PropagateEvent(ev) => {
-                if let Some(t) = self.tag_form.state.handle_input(&ev) {
-                    self.persistent.tags.upsert(t);
-                    return Some(Message::ChangeMode(Mode::Normal));
+            Message::PropagateEvent(ev) => match self.current_mode {
+                Mode::Search => {
+                    self.search.input.handle_event(&ev);
+                    self.search
+                        .update_matches(shown_mods(&self.rows, &self.persistent.mods));
+                    let selected = self.search.best_match().or(self.search.origin);
+                    self.table_state.select(selected);
+                }
+                Mode::Filter => {
+                    self.filter.input.handle_event(&ev);
+                    if self.filter.update().is_ok() {
+                        self.show_filter();
+                    }
+                }
+                Mode::NameProfile => {
+                    if let Some(name) = self.profile_form.state.handle_input(&ev) {
+                        return Some(Message::SaveProfile(name));
+                    }
+                }
+                _ => {
+                    if let Some(t) = self.tag_form.state.handle_input(&ev) {
+                        return Some(Message::SaveTag(t));
+                    };
+                }
+            },
+            Message::SaveTag(tag) => {
+                let selected = self.selected_mod_id();
+                match self.edited_tag.take() {
+                    Some(name) => {
+                        self.persistent.tags.remove(&name);
+                        self.persistent.tags.remove(&tag.name);
+                        self.persistent.mods.replace_tag(&name, Some(&tag));
+                    }
+                    None => {
+                        self.persistent.tags.remove(&tag.name);
+                        self.persistent.mods.replace_tag(&tag.name, Some(&tag));
+                    }
+                }
+                let name = tag.name.clone();
+                self.persistent.tags.upsert(tag);
+                self.reselect_mod(selected);
+                let idx = self.persistent.tags.iter().position(|t| t.name == name);
+                self.list_state.select(idx);
+                return Some(Message::ChangeMode(self.current_mode.parent()));
+            }
+            Message::DeleteTag => {
+                let tag = self
+                    .list_state
+                    .selected()
+                    .and_then(|idx| self.persistent.tags.get(idx))?;
+                let name = tag.name.clone();
+                let selected = self.selected_mod_id();
+                self.persistent.tags.remove(&name);
+                self.persistent.mods.replace_tag(&name, None);
+                self.reselect_mod(selected);
+                self.clamp_tag_list();
+                self.status_line.state.notify(format!("Deleted tag {name}"));
+            }
+            Message::CancelSearch => {
+                self.search.input.reset();
+                self.search
+                    .update_matches(shown_mods(&self.rows, &self.persistent.mods));
+                self.table_state.select(self.search.origin);
+                return Some(Message::ChangeMode(Mode::Normal));
+            }
+            Message::ConfirmFilter => {
+                if let Err(e) = self.filter.update() {
+                    self.status_line
+                        .state
+                        .notify(format!("Invalid filter: {e}"));
+                    return None;
+                }
+                return Some(Message::ChangeMode(Mode::Normal));
+            }
+            Message::CancelFilter | Message::ClearFilter => {
+                let text = match msg {
+                    Message::CancelFilter => self.filter.origin.take().unwrap_or_default(),
+                    _ => String::new(),
                 };
+                self.filter.input = text.into();
+                // the original query was valid
+                let _ = self.filter.update();
+                return Some(Message::ChangeMode(Mode::Normal));
+            }
+            Message::SearchNext(forward) => {
+                let count: usize = self.movement_delta.parse().unwrap_or(1);
+                let from = self.table_state.selected().unwrap_or_default();
+                match self.search.next_match(from, forward, count) {
+                    Some(idx) => self.table_state.select(Some(idx)),
+                    None => self.status_line.state.notify("No matches"),
+                }
+                return Some(Message::ClearCommand);
             }
             Message::InsertTag => {
-                let tag = self
-                    .persistent
-                    .tags
-                    .get(self.list_state.selected().unwrap())
-                    .unwrap();
+                let Some(tag) = self
+                    .list_state
+                    .selected()
+                    .and_then(|idx| self.persistent.tags.get(idx))
+                else {
+                    return Some(Message::ChangeMode(Mode::Normal));
+                };
+                let tag = tag.clone();
                 self.list_state.select_first();
+                let targets = self.target_mod_ids();
+                let selected = self.selected_mod_id();
+                self.persistent.mods.upsert_tag_to(&targets, &tag);
+                self.reselect_mod(selected);
+                return Some(Message::ChangeMode(Mode::Normal));
+            }
+            Message::RemoveTag if matches!(self.current_mode, Mode::Insert) => {
+                let name = self
+                    .list_state
+                    .selected()
+                    .and_then(|idx| self.persistent.tags.get(idx))?
+                    .name
+                    .clone();
+                let targets = self.target_mod_ids();
+                let selected = self.selected_mod_id();
+                let count = self.persistent.mods.remove_tag_from(&targets, &name);
+                self.reselect_mod(selected);
+                self.status_line
+                    .state
+                    .notify(format!("Removed {name} from {count} mods"));
+                return Some(Message::ChangeMode(Mode::Normal));
+            }
+            Message::RemoveTag => {
+                let name = self
+                    .selected_mod()?
+                    .tags()
+                    .get(self.mod_tag_cursor)?
+                    .name
+                    .clone();
+                let selected = self.selected_mod_id();
                 self.persistent
                     .mods
-                    .upsert_tag_to(self.table_state.selected().unwrap(), tag.clone());
+                    .remove_tag_from(&selected.iter().cloned().collect(), &name);
+                self.reselect_mod(selected);
                 return Some(Message::ChangeMode(Mode::Normal));
             }
-            Message::ChangeMode(mode) => {
+            Message::SelectRange => {
+                let cursor = self.table_state.selected()?;
+                self.selection
+                    .toggle_range(shown_mods(&self.rows, &self.persistent.mods), cursor);
+                if matches!(self.current_mode, Mode::Normal) {
+                    return Some(Message::ChangeMode(Mode::Visual));
+                }
+            }
+            Message::ToggleSelected => {
+                let id = self.selected_mod_id()?;
+                self.selection.toggle(&id);
+                if matches!(self.current_mode, Mode::Normal) {
+                    return Some(Message::ChangeMode(Mode::Visual));
+                }
+            }
+            Message::ShiftLoadOrder(up) => {
+                let count: usize = self.movement_delta.parse().unwrap_or(1);
+                let targets: Vec<String> = self.target_mod_ids().into_iter().collect();
+                for _ in 0..count {
+                    self.mods_config.shift(&targets, up);
+                }
+                return Some(Message::ClearCommand);
+            }
+            Message::ToggleActive => {
+                let targets = self.target_mod_ids();
+                // a partly active selection is activated as a whole
+                let activate = !targets.iter().all(|id| self.mods_config.is_active(id));
+                for game_mod in self.persistent.mods.iter() {
+                    if targets.contains(game_mod.identifier()) {
+                        self.mods_config.set_active(game_mod.identifier(), activate);
+                    }
+                }
+            }
+            Message::Undo | Message::Redo => {
+                let selected = self.selected_mod_id();
+                let done = if matches!(msg, Message::Undo) {
+                    self.history
+                        .undo(&mut self.persistent, &mut self.mods_config)
+                        .map(|d| format!("Undid: {d}"))
+                } else {
+                    self.history
+                        .redo(&mut self.persistent, &mut self.mods_config)
+                        .map(|d| format!("Redid: {d}"))
+                };
+                self.reselect_mod(selected);
+                self.clamp_tag_list();
+                self.clamp_profile_list();
+                self.show_filter();
+                self.status_line.state.history = self.history.counts();
+                self.status_line.state.change_hint(
+                    self.current_mode,
+                    &self.movement_delta,
+                    &self.keymap,
+                );
+                self.status_line
+                    .state
+                    .notify(done.unwrap_or_else(|| "Nothing to do".to_owned()));
+            }
+            Message::ScrollDetails(direction) => {
+                let d = self.movement_delta.parse().unwrap_or(1);
+                self.details_scroll = match direction {
+                    MoveDirection::Up => self.details_scroll.saturating_sub(d),
+                    _ => self.details_scroll.saturating_add(d),
+                };
+                return Some(Message::ClearCommand);
+            }
+            Message::ToggleDetails => self.show_details = !self.show_details,
+            Message::SwitchProfile => {
+                let profile = self.highlighted_profile()?.clone();
+                self.store_profile();
+                self.mods_config.active_mods = profile.active_mods;
+                self.persistent.current_profile = Some(profile.name.clone());
+                self.status_line.state.notify(if self.writes_mods_config {
+                    format!(
+                        "Switched to {}, ModsConfig.xml is written on exit",
+                        profile.name
+                    )
+                } else {
+                    format!(
+                        "Switched to {}, there is no ModsConfig.xml to write",
+                        profile.name
+                    )
+                });
+                return Some(Message::ChangeMode(Mode::Normal));
+            }
+            Message::NameProfile(edit) => {
+                let highlighted = self.highlighted_profile().map(|p| p.name.clone());
+                let name = match edit {
+                    ProfileEdit::New => String::new(),
+                    ProfileEdit::Duplicate => format!("{} copy", highlighted.as_ref()?),
+                    ProfileEdit::Rename => highlighted.clone()?,
+                };
+                self.profile_form
+                    .state
+                    .fill(ProfileForm::new(&name, self.theme.mode(Mode::NameProfile)));
+                self.profile_edit = Some((edit, highlighted));
+                return Some(Message::ChangeMode(Mode::NameProfile));
+            }
+            Message::SaveProfile(name) => {
+                let (edit, highlighted) = self.profile_edit.take()?;
+                let renaming_itself =
+                    matches!(edit, ProfileEdit::Rename) && highlighted.as_ref() == Some(&name);
+                if self.persistent.profiles.get_by_name(&name).is_some() && !renaming_itself {
+                    self.status_line
+                        .state
+                        .notify(format!("A profile called {name} already exists"));
+                    self.profile_edit = Some((edit, highlighted));
+                    return None;
+                }
+                match edit {
+                    ProfileEdit::New => {
+                        self.store_profile();
+                        self.persistent.profiles.upsert(Profile {
+                            name: name.clone(),
+                            active_mods: self.mods_config.active_mods.clone(),
+                        });
+                        self.persistent.current_profile = Some(name.clone());
+                    }
+                    ProfileEdit::Duplicate => {
+                        self.store_profile();
+                        let source = self.persistent.profiles.get_by_name(&highlighted?)?;
+                        self.persistent.profiles.upsert(Profile {
+                            name: name.clone(),
+                            active_mods: source.active_mods.clone(),
+                        });
+                    }
+                    ProfileEdit::Rename => {
+                        let old = highlighted?;
+                        let mut profile = self.persistent.profiles.remove(&old)?;
+                        profile.name = name.clone();
+                        self.persistent.profiles.upsert(profile);
+                        if self.persistent.current_profile.as_ref() == Some(&old) {
+                            self.persistent.current_profile = Some(name.clone());
+                        }
+                        if self.diff_base.as_ref() == Some(&old) {
+                            self.diff_base = Some(name.clone());
+                        }
+                    }
+                }
+                let idx = self.persistent.profiles.iter().position(|p| p.name == name);
+                self.profiles_state.select(idx);
+                return Some(Message::ChangeMode(Mode::Profiles));
+            }
+            Message::MarkProfile => {
+                let name = self.highlighted_profile()?.name.clone();
+                self.diff_base = match self.diff_base.take() {
+                    Some(base) if base == name => None,
+                    _ => Some(name),
+                };
+            }
+            Message::DiffProfile => {
+                let profile = self.highlighted_profile()?;
+                let (base_title, base) = match &self.diff_base {
+                    Some(base) => {
+                        let base = self.persistent.profiles.get_by_name(base)?;
+                        (base.name.clone(), &base.active_mods)
+                    }
+                    None => ("ModsConfig.xml".to_owned(), &self.mods_config.active_mods),
+                };
+                let diff = LoadOrderDiff::new(base, &profile.active_mods);
+                self.diff_titles = (base_title, profile.name.clone());
+                self.diff = diff;
+                return Some(Message::ChangeMode(Mode::Diff));
+            }
+            Message::Export => {
+                let idx = self.export_state.selected()?;
+                let (_, path) = export_targets().nth(idx)?;
+                let (text, count) = match Format::ALL.get(idx) {
+                    Some(&format) => (
+                        export::export(format, self.shown_mods(), &self.persistent.tags),
+                        self.rows.len(),
+                    ),
+                    None => {
+                        let format = ListFormat::ALL[idx - Format::ALL.len()];
+                        let active_mods = &self.mods_config.active_mods;
+                        let text = mod_list::write(
+                            format,
+                            active_mods,
+                            &self.persistent.mods,
+                            &self.mods_config,
+                        );
+                        match text {
+                            Ok(text) => (text, active_mods.len()),
+                            Err(e) => {
+                                error!("Couldn't export the load order: {e}");
+                                self.status_line
+                                    .state
+                                    .notify(format!("Couldn't export the load order: {e}"));
+                                return None;
+                            }
+                        }
+                    }
+                };
+                let text = match std::fs::write(&path, text) {
+                    Ok(()) => format!("Exported {count} mods to {path}"),
+                    Err(e) => {
+                        error!("Couldn't write {path}: {e}");
+                        format!("Couldn't write {path}: {e}")
+                    }
+                };
+                self.status_line.state.notify(text);
+                return Some(Message::ChangeMode(Mode::Normal));
+            }
+            Message::ImportSave => {
+                let (path, meta) = self.saves.get(self.saves_state.selected()?)?;
+                let Some(meta) = meta else {
+                    self.status_line
+                        .state
+                        .notify(format!("{} has no mod list", path.display()));
+                    return None;
+                };
+                let name = path.file_stem()?.to_string_lossy().into_owned();
+                let missing = meta.missing(self.persistent.mods.iter()).len();
+                let profile = Profile {
+                    name: name.clone(),
+                    active_mods: meta.active_mods(),
+                };
+                let verb = if self.persistent.profiles.get_by_name(&name).is_some() {
+                    "Updated"
+                } else {
+                    "Created"
+                };
+                self.persistent.profiles.upsert(profile);
+                let idx = self.persistent.profiles.iter().position(|p| p.name == name);
+                self.profiles_state.select(idx);
+                self.status_line.state.notify(format!(
+                    "{verb} profile {name}, {missing} of its mods aren't installed"
+                ));
+                return Some(Message::ChangeMode(Mode::Profiles));
+            }
+            Message::DeleteProfile => {
+                let name = self.highlighted_profile()?.name.clone();
+                self.persistent.profiles.remove(&name);
+                if self.persistent.current_profile.as_ref() == Some(&name) {
+                    self.persistent.current_profile = None;
+                }
+                if self.diff_base.as_ref() == Some(&name) {
+                    self.diff_base = None;
+                }
+                self.clamp_profile_list();
+                self.show_filter();
+                self.status_line
+                    .state
+                    .notify(format!("Deleted profile {name}, 'u' in NORMAL undoes it"));
+            }
+            Message::Rescan => self.rescan(),
+            Message::SortLoadOrder => {
+                self.mods_config.active_mods =
+                    sorter::sort_load_order(&self.persistent.mods, &self.mods_config);
+                if !sorter::find_cycles(&self.persistent.mods, &self.mods_config).is_empty() {
+                    return Some(Message::ChangeMode(Mode::ShowCycles));
+                }
+            }
+            Message::JumpToIssue => {
+                let issue = self
+                    .issues_state
+                    .selected()
+                    .and_then(|idx| self.issues.get(idx));
+                if let Some(issue) = issue {
+                    let row = self
+                        .shown_mods()
+                        .position(|m| m.metadata.package_id == issue.package_id);
+                    match row {
+                        Some(_) => self.table_state.select(row),
+                        None => self.status_line.state.notify("The mod is filtered out"),
+                    }
+                }
+                return Some(Message::ChangeMode(Mode::Normal));
+            }
+            Message::ChangeMode(mut mode) => {
+                // leaving the tag list goes back to the selection it applies to
+                if matches!(mode, Mode::Normal)
+                    && matches!(self.current_mode, Mode::Insert)
+                    && !self.selection.is_empty()
+                {
+                    mode = Mode::Visual;
+                }
+                if matches!(mode, Mode::Normal) {
+                    self.selection.clear();
+                }
+                if matches!(mode, Mode::RemoveTag) {
+                    let has_tags = self.selected_mod().is_some_and(|m| !m.tags().is_empty());
+                    if !has_tags {
+                        self.status_line
+                            .state
+                            .notify("The selected mod has no tags");
+                        return None;
+                    }
+                    self.mod_tag_cursor = 0;
+                }
+                if matches!(mode, Mode::EditTag) {
+                    let tag = self
+                        .list_state
+                        .selected()
+                        .and_then(|idx| self.persistent.tags.get(idx))?;
+                    self.tag_form.state.fill(TagForm::from(tag));
+                    self.edited_tag = Some(tag.name.clone());
+                }
                 self.status_line.state.change_mode(mode);
                 self.status_line
                     .state
-                    .change_hint(mode, &self.movement_delta);
+                    .change_hint(mode, &self.movement_delta, &self.keymap);
                 // TODO: Generalize
                 if matches!(mode, Mode::CreateTag) {
                     self.tag_form.state.reset();
+                    self.edited_tag = None;
+                }
+                if matches!(mode, Mode::ShowTags | Mode::Insert)
+                    && self.list_state.selected().is_none()
+                    && !self.persistent.tags.is_empty()
+                {
+                    self.list_state.select_first();
+                }
+                if matches!(mode, Mode::ShowCycles) {
+                    self.cycles = sorter::find_cycles(&self.persistent.mods, &self.mods_config);
+                }
+                if matches!(
+                    mode,
+                    Mode::ShowCycles | Mode::ScanErrors | Mode::Help | Mode::Diff
+                ) {
+                    self.popup_scroll = 0;
+                }
+                if matches!(mode, Mode::Search) {
+                    self.search.input.reset();
+                    self.search
+                        .update_matches(shown_mods(&self.rows, &self.persistent.mods));
+                    self.search.origin = self.table_state.selected();
+                }
+                if matches!(mode, Mode::Profiles) && self.profiles_state.selected().is_none() {
+                    let current = self.persistent.current_profile.as_ref();
+                    let idx = self
+                        .persistent
+                        .profiles
+                        .iter()
+                        .position(|p| Some(&p.name) == current);
+                    self.profiles_state.select(idx);
+                    if idx.is_none() && !self.persistent.profiles.is_empty() {
+                        self.profiles_state.select_first();
+                    }
+                }
+                if matches!(mode, Mode::Filter) {
+                    let text = self.filter.active.as_ref().map(|q| q.text().to_owned());
+                    self.filter.input = text.clone().unwrap_or_default().into();
+                    self.filter.origin = text;
+                }
+                if matches!(mode, Mode::Export) && self.export_state.selected().is_none() {
+                    self.export_state.select_first();
+                }
+                if matches!(mode, Mode::Saves) {
+                    self.read_saves();
+                    self.saves_state = ListState::default();
+                    if !self.saves.is_empty() {
+                        self.saves_state.select_first();
+                    }
+                }
+                if matches!(mode, Mode::Issues) {
+                    self.issues = validation::validate(&self.persistent.mods, &self.mods_config);
+                    self.issues_state = ListState::default();
+                    if !self.issues.is_empty() {
+                        self.issues_state.select_first();
+                    }
                 }
                 self.current_mode = mode;
+                self.show_filter();
                 return Some(Message::ClearCommand);
             }
             Message::Exit => self.should_close = true,
@@ -283,3 +1651,149 @@ impl Model {
         None
     }
 }
+/// The mods at `rows`, borrowing only what's needed.
+fn shown_mods<'a>(
+    rows: &'a [usize],
+    mods: &'a OrderedItems<Mod>,
+) -> impl Iterator<Item = &'a Mod> + Clone {
+    rows.iter().filter_map(|&idx| mods.get(idx))
+}
+/// Entries of [`Mode::Export`] and the file each is written to, the shown
+/// mods in every [`Format`] then the load order in every [`ListFormat`].
+fn export_targets() -> impl Iterator<Item = (&'static str, String)> {
+    let mods = Format::ALL
+        .into_iter()
+        .map(|f| (f.str_repr(), format!("./mod_list.{}", f.extension())));
+    let load_orders = ListFormat::ALL.into_iter().map(|f| {
+        let name = match f {
+            ListFormat::RimSort => "RimSort load order",
+            ListFormat::RimPy => "RimPy load order",
+        };
+        (
+            name,
+            format!("./load_order.{}.xml", f.str_repr().to_lowercase()),
+        )
+    });
+    mods.chain(load_orders)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+    use crate::mods::{game::SavedMod, test_util::config};
+
+    fn model(profiles: &[&str]) -> Model {
+        let persistent = Persistent {
+            profiles: profiles
+                .iter()
+                .map(|name| Profile {
+                    name: (*name).to_owned(),
+                    active_mods: vec![format!("{name}.mod")],
+                })
+                .collect::<Vec<_>>()
+                .into(),
+            ..Default::default()
+        };
+        Model::new(
+            persistent,
+            Some(config(&[])),
+            vec![],
+            PathBuf::new(),
+            Keymap::new(&HashMap::new()).ok().unwrap(),
+            Theme::default(),
+        )
+    }
+    /// Handles `msg` and everything it leads to, like the main loop.
+    fn send(model: &mut Model, msg: Message) {
+        let mut msg = Some(msg);
+        while let Some(next) = msg {
+            msg = model.update(next);
+        }
+    }
+    fn status_line(model: &mut Model) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(240, 20)).unwrap();
+        terminal.draw(|f| model.view(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let y = buffer.area.height - 1;
+        (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect()
+    }
+    fn highlight_profile(model: &mut Model, name: &str) {
+        send(model, Message::ChangeMode(Mode::Profiles));
+        let idx = model
+            .persistent
+            .profiles
+            .iter()
+            .position(|p| p.name == name);
+        model.profiles_state.select(idx);
+    }
+
+    #[test]
+    fn undoable_edits_keep_their_notification() {
+        let mut model = model(&["first", "second"]);
+        highlight_profile(&mut model, "second");
+        send(&mut model, Message::DeleteProfile);
+        let line = status_line(&mut model);
+        assert!(line.contains("Deleted profile second"), "{line}");
+
+        // the undo count shows once the notification is dismissed
+        model.dismiss_notification();
+        let line = status_line(&mut model);
+        assert!(line.contains("1 undo, 0 redo"), "{line}");
+        assert!(!line.contains("Deleted profile"), "{line}");
+    }
+
+    #[test]
+    fn profile_notifications_are_shown() {
+        let mut model = model(&["first", "second"]);
+        highlight_profile(&mut model, "first");
+        send(&mut model, Message::SwitchProfile);
+        assert_eq!(model.current_mode, Mode::Normal);
+        assert_eq!(model.mods_config.active_mods, ["first.mod"]);
+        let line = status_line(&mut model);
+        assert!(
+            line.contains("Switched to first, ModsConfig.xml is written on exit"),
+            "{line}"
+        );
+
+        highlight_profile(&mut model, "first");
+        send(&mut model, Message::DeleteProfile);
+        assert!(model.persistent.current_profile.is_none());
+        let line = status_line(&mut model);
+        assert!(
+            line.contains("Deleted profile first, 'u' in NORMAL undoes it"),
+            "{line}"
+        );
+    }
+
+    #[test]
+    fn import_summary_is_shown() {
+        let mut model = model(&[]);
+        let meta = SaveMeta {
+            game_version: "1.5.4104 rev435".to_owned(),
+            mods: ["ludeon.rimworld", "brrainz.harmony"]
+                .map(|id| SavedMod {
+                    package_id: id.to_owned(),
+                    steam_id: None,
+                    name: id.to_owned(),
+                })
+                .to_vec(),
+        };
+        send(&mut model, Message::ChangeMode(Mode::Profiles));
+        send(&mut model, Message::ChangeMode(Mode::Saves));
+        model.saves = vec![(PathBuf::from("colony.rws"), Some(meta))];
+        model.saves_state.select(Some(0));
+        send(&mut model, Message::ImportSave);
+        assert_eq!(model.current_mode, Mode::Profiles);
+        let line = status_line(&mut model);
+        assert!(
+            line.contains("Created profile colony, 2 of its mods aren't installed"),
+            "{line}"
+        );
+    }
+}
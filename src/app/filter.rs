@@ -0,0 +1,309 @@
+//! Filter over the mod table
+//!
+//! A query is made of terms joined by `and`, `or` and `not`, grouped with
+//! parentheses, e.g. `tag:qol and not tag:broken or author:Oskar`.
+//!
+//! - `tag:name` mods carrying the tag
+//! - `author:`, `name:`, `id:` case insensitive substrings of the authors,
+//!   name or packageId, a bare word looks in the name
+//! - `source:game`, `source:local`, `source:steam`
+//! - `is:active`, `is:missing`
+//!
+//! Values with spaces are quoted, `author:"Kiri W"`. `and` binds tighter
+//! than `or`.
+use tui_input::Input;
+
+use crate::mods::{Item, Mod, game::ModsConfigData, scan::Source};
+
+enum Term {
+    Tag(String),
+    Author(String),
+    Name(String),
+    Id(String),
+    Source(Source),
+    Active,
+    Missing,
+}
+impl Term {
+    fn parse(word: &str) -> Result<Self, String> {
+        let Some((key, value)) = word.split_once(':') else {
+            return Ok(Term::Name(word.to_lowercase()));
+        };
+        let value = value.to_lowercase();
+        if value.is_empty() {
+            return Err(format!("`{key}:` needs a value"));
+        }
+        match key {
+            "tag" => Ok(Term::Tag(value)),
+            "author" => Ok(Term::Author(value)),
+            "name" => Ok(Term::Name(value)),
+            "id" => Ok(Term::Id(value)),
+            "source" => match value.as_str() {
+                "game" => Ok(Term::Source(Source::Game)),
+                "local" => Ok(Term::Source(Source::Local)),
+                "steam" | "workshop" => Ok(Term::Source(Source::Workshop)),
+                _ => Err(format!("unknown source `{value}`")),
+            },
+            "is" => match value.as_str() {
+                "active" => Ok(Term::Active),
+                "missing" => Ok(Term::Missing),
+                _ => Err(format!("unknown state `{value}`")),
+            },
+            _ => Err(format!("unknown key `{key}`")),
+        }
+    }
+    fn matches(&self, game_mod: &Mod, config: &ModsConfigData) -> bool {
+        let metadata = &game_mod.metadata;
+        match self {
+            Term::Tag(name) => game_mod
+                .tags()
+                .iter()
+                .any(|t| t.name.to_lowercase() == *name),
+            Term::Author(author) => metadata
+                .author_names()
+                .any(|a| a.to_lowercase().contains(author)),
+            Term::Name(name) => metadata.name.to_lowercase().contains(name),
+            Term::Id(id) => game_mod.identifier().to_lowercase().contains(id),
+            Term::Source(source) => game_mod.source == *source,
+            Term::Active => config.is_active(game_mod.identifier()),
+            Term::Missing => game_mod.missing,
+        }
+    }
+}
+
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+impl Expr {
+    fn matches(&self, game_mod: &Mod, config: &ModsConfigData) -> bool {
+        match self {
+            Expr::Or(a, b) => a.matches(game_mod, config) || b.matches(game_mod, config),
+            Expr::And(a, b) => a.matches(game_mod, config) && b.matches(game_mod, config),
+            Expr::Not(e) => !e.matches(game_mod, config),
+            Expr::Term(term) => term.matches(game_mod, config),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Word(String),
+}
+fn tokenize(query: &str) -> Result<Vec<Token>, String> {
+    let mut res = vec![];
+    let mut chars = query.chars().peekable();
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            '(' | ')' => {
+                chars.next();
+                res.push(if ch == '(' { Token::Open } else { Token::Close });
+            }
+            ch if ch.is_whitespace() => {
+                chars.next();
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&ch) = chars.peek() {
+                    if !quoted && (ch.is_whitespace() || ch == '(' || ch == ')') {
+                        break;
+                    }
+                    chars.next();
+                    match ch {
+                        '"' => quoted = !quoted,
+                        ch => word.push(ch),
+                    }
+                }
+                if quoted {
+                    return Err("unclosed quote".to_owned());
+                }
+                res.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(res)
+}
+
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+impl Parser {
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.tokens.peek(), Some(Token::Word(w)) if w == keyword);
+        if found {
+            self.tokens.next();
+        }
+        found
+    }
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut res = self.and()?;
+        while self.keyword("or") {
+            res = Expr::Or(Box::new(res), Box::new(self.and()?));
+        }
+        Ok(res)
+    }
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut res = self.not()?;
+        while self.keyword("and") {
+            res = Expr::And(Box::new(res), Box::new(self.not()?));
+        }
+        Ok(res)
+    }
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        match self.tokens.next() {
+            Some(Token::Open) => {
+                let res = self.or()?;
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(res),
+                    _ => Err("missing `)`".to_owned()),
+                }
+            }
+            Some(Token::Word(word)) if !matches!(word.as_str(), "and" | "or") => {
+                Ok(Expr::Term(Term::parse(&word)?))
+            }
+            Some(Token::Word(word)) => Err(format!("expected a term before `{word}`")),
+            Some(Token::Close) => Err("unexpected `)`".to_owned()),
+            None => Err("expected a term".to_owned()),
+        }
+    }
+}
+
+/// A parsed query.
+pub struct Query {
+    text: String,
+    expr: Expr,
+}
+impl Query {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?.into_iter().peekable(),
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.next() {
+            return Err(match token {
+                Token::Close => "unexpected `)`".to_owned(),
+                Token::Open => "expected `and` or `or` before `(`".to_owned(),
+                Token::Word(word) => format!("expected `and` or `or` before `{word}`"),
+            });
+        }
+        Ok(Self {
+            text: text.trim().to_owned(),
+            expr,
+        })
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn matches(&self, game_mod: &Mod, config: &ModsConfigData) -> bool {
+        self.expr.matches(game_mod, config)
+    }
+}
+
+#[derive(Default)]
+pub struct Filter {
+    pub input: Input,
+    /// Query the table is filtered with.
+    pub active: Option<Query>,
+    /// Query when editing started, restored on cancel.
+    pub origin: Option<String>,
+}
+impl Filter {
+    /// Applies the input if it parses, an empty one removes the filter.
+    pub fn update(&mut self) -> Result<(), String> {
+        if self.input.value().trim().is_empty() {
+            self.active = None;
+            return Ok(());
+        }
+        self.active = Some(Query::parse(self.input.value())?);
+        Ok(())
+    }
+    pub fn matches(&self, game_mod: &Mod, config: &ModsConfigData) -> bool {
+        self.active
+            .as_ref()
+            .is_none_or(|query| query.matches(game_mod, config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `expr` with every `and` and `or` parenthesized.
+    fn show(expr: &Expr) -> String {
+        match expr {
+            Expr::Or(a, b) => format!("({} or {})", show(a), show(b)),
+            Expr::And(a, b) => format!("({} and {})", show(a), show(b)),
+            Expr::Not(e) => format!("not {}", show(e)),
+            Expr::Term(term) => match term {
+                Term::Tag(v) => format!("tag:{v}"),
+                Term::Author(v) => format!("author:{v}"),
+                Term::Name(v) => format!("name:{v}"),
+                Term::Id(v) => format!("id:{v}"),
+                Term::Source(source) => format!("source:{}", source.str_repr()),
+                Term::Active => "is:active".to_owned(),
+                Term::Missing => "is:missing".to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn queries_parse() {
+        let cases = [
+            ("Harmony", "name:harmony"),
+            (
+                "tag:qol and not tag:broken or author:Oskar",
+                "((tag:qol and not tag:broken) or author:oskar)",
+            ),
+            ("a or b and c", "(name:a or (name:b and name:c))"),
+            ("a and b and c", "((name:a and name:b) and name:c)"),
+            ("not a and b", "(not name:a and name:b)"),
+            ("not not a", "not not name:a"),
+            ("not (a or b)", "not (name:a or name:b)"),
+            ("(a or b) and c", "((name:a or name:b) and name:c)"),
+            ("((a))", "name:a"),
+            ("author:\"Kiri W\"", "author:kiri w"),
+            ("name:\"a (b)\" or id:x", "(name:a (b) or id:x)"),
+            ("source:steam and is:active", "(source:Steam and is:active)"),
+            ("is:missing", "is:missing"),
+        ];
+        for (text, expected) in cases {
+            let query = Query::parse(text).unwrap_or_else(|e| panic!("{text}: {e}"));
+            assert_eq!(show(&query.expr), expected, "{text}");
+        }
+        assert_eq!(Query::parse("  a or b ").unwrap().text(), "a or b");
+    }
+
+    #[test]
+    fn invalid_queries_are_explained() {
+        let cases = [
+            ("", "expected a term"),
+            ("a and", "expected a term"),
+            ("and a", "expected a term before `and`"),
+            ("a or or b", "expected a term before `or`"),
+            ("(a", "missing `)`"),
+            ("a)", "unexpected `)`"),
+            (")", "unexpected `)`"),
+            ("a b", "expected `and` or `or` before `b`"),
+            ("a (b)", "expected `and` or `or` before `(`"),
+            ("author:\"Kiri", "unclosed quote"),
+            ("tag:", "`tag:` needs a value"),
+            ("color:red", "unknown key `color`"),
+            ("source:gog", "unknown source `gog`"),
+            ("is:broken", "unknown state `broken`"),
+        ];
+        for (text, expected) in cases {
+            match Query::parse(text) {
+                Ok(query) => panic!("{text} parsed as {}", show(&query.expr)),
+                Err(e) => assert_eq!(e, expected, "{text}"),
+            }
+        }
+    }
+}
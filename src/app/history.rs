@@ -0,0 +1,322 @@
+//! Undo and redo of the changes made to [`Persistent`] and the load order
+//!
+//! Messages that edit them are wrapped by a [`Snapshot`] of the tags, the
+//! profiles and the load order before and after, their difference is the
+//! [`Edit`] that gets reverted.
+use std::collections::{HashMap, VecDeque};
+
+use super::Persistent;
+use crate::mods::{Item, OrderedItems, Profile, Tag, game::ModsConfigData};
+
+/// Edits kept, the oldest ones are forgotten first.
+const LIMIT: usize = 100;
+
+/// Tags of the registry and of every mod, the profiles and the active mods.
+pub struct Snapshot {
+    tags: Vec<Tag>,
+    mods: HashMap<String, Vec<String>>,
+    profiles: Vec<Profile>,
+    current_profile: Option<String>,
+    load_order: Vec<String>,
+}
+impl Snapshot {
+    pub fn new(persistent: &Persistent, mods_config: &ModsConfigData) -> Self {
+        Self {
+            tags: persistent.tags.iter().cloned().collect(),
+            mods: persistent
+                .mods
+                .iter()
+                .map(|m| {
+                    let names = m.tags().iter().map(|t| t.name.clone()).collect();
+                    (m.identifier().to_owned(), names)
+                })
+                .collect(),
+            profiles: persistent.profiles.iter().cloned().collect(),
+            current_profile: persistent.current_profile.clone(),
+            load_order: mods_config.active_mods.clone(),
+        }
+    }
+}
+
+/// A reversible change, every pair holds the state before and after.
+struct Edit {
+    /// Registry entries sharing a name, `None` if it didn't exist.
+    tags: Vec<(Option<Tag>, Option<Tag>)>,
+    /// Tag names of the mods by packageId.
+    mods: Vec<(String, Vec<String>, Vec<String>)>,
+    /// Profiles sharing a name, `None` if it didn't exist.
+    profiles: Vec<(Option<Profile>, Option<Profile>)>,
+    /// `None` if the current profile didn't change.
+    current_profile: Option<(Option<String>, Option<String>)>,
+    /// Active mods, `None` if they didn't change.
+    load_order: Option<(Vec<String>, Vec<String>)>,
+}
+impl Edit {
+    fn new(before: Snapshot, after: Snapshot) -> Self {
+        let tags = changed(&before.tags, &after.tags, |b, a| {
+            b.score == a.score && b.color == a.color
+        });
+        let mut mods: Vec<_> = after
+            .mods
+            .into_iter()
+            .filter_map(|(id, a)| {
+                let b = before.mods.get(&id).cloned().unwrap_or_default();
+                (b != a).then_some((id, b, a))
+            })
+            .collect();
+        mods.sort();
+        let profiles = changed(&before.profiles, &after.profiles, |b, a| {
+            b.active_mods == a.active_mods
+        });
+        let current_profile = (before.current_profile != after.current_profile)
+            .then_some((before.current_profile, after.current_profile));
+        let load_order = (before.load_order != after.load_order)
+            .then_some((before.load_order, after.load_order));
+        Self {
+            tags,
+            mods,
+            profiles,
+            current_profile,
+            load_order,
+        }
+    }
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.mods.is_empty()
+            && self.profiles.is_empty()
+            && self.current_profile.is_none()
+            && self.load_order.is_none()
+    }
+    fn description(&self) -> String {
+        if let Some(res) = describe(&self.tags, "tag") {
+            return res;
+        }
+        let added_or_removed = self
+            .profiles
+            .iter()
+            .any(|(b, a)| b.is_none() || a.is_none());
+        match (&self.current_profile, describe(&self.profiles, "profile")) {
+            // switching also saves the load order into the previous profile
+            (Some((_, Some(name))), _) if !added_or_removed => format!("switch to profile {name}"),
+            (_, Some(res)) => res,
+            _ if !self.mods.is_empty() => format!("change the tags of {} mods", self.mods.len()),
+            _ => "change the load order".to_owned(),
+        }
+    }
+    /// Brings `persistent` and `mods_config` to the state before the edit,
+    /// or after it if `forward`.
+    fn apply(&self, persistent: &mut Persistent, mods_config: &mut ModsConfigData, forward: bool) {
+        apply(&mut persistent.tags, &self.tags, forward);
+        for (package_id, before, after) in &self.mods {
+            let names = if forward { after } else { before };
+            persistent.mods.set_tags(package_id, names);
+        }
+        persistent.mods.sync_tags(&persistent.tags);
+        apply(&mut persistent.profiles, &self.profiles, forward);
+        if let Some((before, after)) = &self.current_profile {
+            persistent.current_profile = if forward { after } else { before }.clone();
+        }
+        if let Some((before, after)) = &self.load_order {
+            mods_config.active_mods = if forward { after } else { before }.clone();
+        }
+    }
+}
+
+/// Items of `before` and `after` sharing a name that were added, removed or
+/// aren't `same`.
+fn changed<T: Item + Clone>(
+    before: &[T],
+    after: &[T],
+    same: impl Fn(&T, &T) -> bool,
+) -> Vec<(Option<T>, Option<T>)> {
+    let find = |items: &[T], name: &str| items.iter().find(|t| t.identifier() == name).cloned();
+    let mut names: Vec<&str> = before.iter().chain(after).map(|t| t.identifier()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| match (find(before, name), find(after, name)) {
+            (Some(b), Some(a)) if same(&b, &a) => None,
+            pair => Some(pair),
+        })
+        .collect()
+}
+/// What happened to the `kind` of items in `changes`, `None` if nothing did.
+fn describe<T: Item>(changes: &[(Option<T>, Option<T>)], kind: &str) -> Option<String> {
+    let created = changes
+        .iter()
+        .find_map(|(b, a)| a.as_ref().filter(|_| b.is_none()));
+    let deleted = changes
+        .iter()
+        .find_map(|(b, a)| b.as_ref().filter(|_| a.is_none()));
+    let edited = changes.iter().find_map(|(b, a)| a.as_ref().and(b.as_ref()));
+    Some(match (created, deleted, edited) {
+        (Some(new), Some(old), _) => {
+            format!("rename {kind} {} to {}", old.identifier(), new.identifier())
+        }
+        (Some(new), None, _) => format!("create {kind} {}", new.identifier()),
+        (None, Some(old), _) => format!("delete {kind} {}", old.identifier()),
+        (None, None, Some(item)) => format!("edit {kind} {}", item.identifier()),
+        (None, None, None) => return None,
+    })
+}
+/// Replaces the items of `changes` by their state before or after.
+fn apply<T: Item + Clone>(
+    items: &mut OrderedItems<T>,
+    changes: &[(Option<T>, Option<T>)],
+    forward: bool,
+) {
+    for (before, after) in changes {
+        let (old, new) = if forward {
+            (before, after)
+        } else {
+            (after, before)
+        };
+        if let Some(old) = old {
+            items.remove(old.identifier());
+        }
+        if let Some(new) = new {
+            items.upsert(new.clone());
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+}
+impl History {
+    /// Saves what changed since `before`, returns `false` if nothing did.
+    pub fn record(
+        &mut self,
+        before: Snapshot,
+        persistent: &Persistent,
+        mods_config: &ModsConfigData,
+    ) -> bool {
+        let edit = Edit::new(before, Snapshot::new(persistent, mods_config));
+        if edit.is_empty() {
+            return false;
+        }
+        if self.undo.len() == LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(edit);
+        self.redo.clear();
+        true
+    }
+    /// Reverts the last edit, returns what it did.
+    pub fn undo(
+        &mut self,
+        persistent: &mut Persistent,
+        mods_config: &mut ModsConfigData,
+    ) -> Option<String> {
+        let edit = self.undo.pop_back()?;
+        edit.apply(persistent, mods_config, false);
+        let res = edit.description();
+        self.redo.push(edit);
+        Some(res)
+    }
+    /// Reapplies the last undone edit, returns what it did.
+    pub fn redo(
+        &mut self,
+        persistent: &mut Persistent,
+        mods_config: &mut ModsConfigData,
+    ) -> Option<String> {
+        let edit = self.redo.pop()?;
+        edit.apply(persistent, mods_config, true);
+        let res = edit.description();
+        self.undo.push_back(edit);
+        Some(res)
+    }
+    /// Edits that can be undone and redone.
+    pub fn counts(&self) -> (usize, usize) {
+        (self.undo.len(), self.redo.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, active_mods: &[&str]) -> Profile {
+        Profile {
+            name: name.to_owned(),
+            active_mods: active_mods.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+    fn names(persistent: &Persistent) -> Vec<&str> {
+        persistent
+            .profiles
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn switching_profiles_is_undone_with_the_load_order() {
+        let mut persistent = Persistent {
+            profiles: vec![profile("a", &["x"]), profile("b", &["y", "z"])].into(),
+            current_profile: Some("a".to_owned()),
+            ..Default::default()
+        };
+        let mut config = ModsConfigData {
+            active_mods: vec!["x".to_owned(), "new".to_owned()],
+            ..Default::default()
+        };
+        let mut history = History::default();
+
+        let before = Snapshot::new(&persistent, &config);
+        persistent.profiles.upsert(profile("a", &["x", "new"]));
+        persistent.current_profile = Some("b".to_owned());
+        config.active_mods = vec!["y".to_owned(), "z".to_owned()];
+        assert!(history.record(before, &persistent, &config));
+
+        assert_eq!(
+            history.undo(&mut persistent, &mut config).as_deref(),
+            Some("switch to profile b")
+        );
+        assert_eq!(persistent.current_profile.as_deref(), Some("a"));
+        assert_eq!(config.active_mods, ["x", "new"]);
+        assert_eq!(
+            persistent.profiles.get_by_name("a"),
+            Some(&profile("a", &["x"]))
+        );
+
+        history.redo(&mut persistent, &mut config);
+        assert_eq!(persistent.current_profile.as_deref(), Some("b"));
+        assert_eq!(config.active_mods, ["y", "z"]);
+        assert_eq!(
+            persistent.profiles.get_by_name("a"),
+            Some(&profile("a", &["x", "new"]))
+        );
+    }
+
+    #[test]
+    fn deleted_profiles_come_back() {
+        let mut persistent = Persistent {
+            profiles: vec![profile("a", &["x"]), profile("b", &["y"])].into(),
+            current_profile: Some("b".to_owned()),
+            ..Default::default()
+        };
+        let mut config = ModsConfigData::default();
+        let mut history = History::default();
+
+        let before = Snapshot::new(&persistent, &config);
+        persistent.profiles.remove("b");
+        persistent.current_profile = None;
+        assert!(history.record(before, &persistent, &config));
+        // nothing changed
+        let before = Snapshot::new(&persistent, &config);
+        assert!(!history.record(before, &persistent, &config));
+
+        assert_eq!(
+            history.undo(&mut persistent, &mut config).as_deref(),
+            Some("delete profile b")
+        );
+        assert_eq!(names(&persistent), ["a", "b"]);
+        assert_eq!(persistent.current_profile.as_deref(), Some("b"));
+        assert_eq!(history.counts(), (0, 1));
+        assert!(history.undo(&mut persistent, &mut config).is_none());
+    }
+}
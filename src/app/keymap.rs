@@ -0,0 +1,667 @@
+//! Key bindings
+//!
+//! Every [`Mode`] maps sequences of keys, written vim style (`gg`, `<C-r>`,
+//! `<Esc>`), to an [`Action`]. [`defaults`] may be overridden per mode from
+//! `config.ron`, binding a sequence to `"Unbind"` removes it.
+use std::{collections::HashMap, fmt};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::{
+    Mode,
+    messages::{Message, MoveDirection, ProfileEdit},
+};
+
+/// A single key press, shift is part of the character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    ctrl: bool,
+    alt: bool,
+}
+impl From<KeyEvent> for Key {
+    fn from(ev: KeyEvent) -> Self {
+        Self {
+            code: ev.code,
+            ctrl: ev.modifiers.contains(KeyModifiers::CONTROL),
+            alt: ev.modifiers.contains(KeyModifiers::ALT),
+        }
+    }
+}
+impl Key {
+    /// Parses `gg`, `<C-r>`, `<Esc>`... into the keys to press.
+    fn parse_sequence(text: &str) -> Result<Vec<Key>, String> {
+        let mut keys = vec![];
+        let mut chars = text.chars();
+        while let Some(ch) = chars.next() {
+            if ch != '<' {
+                keys.push(Key::plain(KeyCode::Char(ch)));
+                continue;
+            }
+            let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+            keys.push(Key::parse_special(&name)?);
+        }
+        if keys.is_empty() {
+            return Err("empty key sequence".to_owned());
+        }
+        Ok(keys)
+    }
+    fn parse_special(name: &str) -> Result<Key, String> {
+        if let Some((modifier, rest)) = name.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+            let mut key = Key::parse_special(rest)?;
+            match modifier {
+                "C" | "c" => key.ctrl = true,
+                "A" | "a" | "M" | "m" => key.alt = true,
+                _ => return Err(format!("unknown modifier `{modifier}`")),
+            }
+            return Ok(key);
+        }
+        let code = match name {
+            "Esc" => KeyCode::Esc,
+            "Enter" | "CR" => KeyCode::Enter,
+            "Tab" => KeyCode::Tab,
+            "BS" => KeyCode::Backspace,
+            "Del" => KeyCode::Delete,
+            "Space" => KeyCode::Char(' '),
+            "lt" => KeyCode::Char('<'),
+            "Up" => KeyCode::Up,
+            "Down" => KeyCode::Down,
+            "Left" => KeyCode::Left,
+            "Right" => KeyCode::Right,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) => KeyCode::Char(ch),
+                    (Some('F'), Some(_)) => match name[1..].parse() {
+                        Ok(n) => KeyCode::F(n),
+                        Err(_) => return Err(format!("unknown key `<{name}>`")),
+                    },
+                    _ => return Err(format!("unknown key `<{name}>`")),
+                }
+            }
+        };
+        Ok(Key::plain(code))
+    }
+    const fn plain(code: KeyCode) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            alt: false,
+        }
+    }
+    /// The digit typed as a count, if any.
+    pub fn digit(&self) -> Option<char> {
+        match self.code {
+            KeyCode::Char(c) if c.is_ascii_digit() && !self.ctrl && !self.alt => Some(c),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_owned(),
+            KeyCode::Char('<') => "lt".to_owned(),
+            KeyCode::Char(c) if !self.ctrl && !self.alt => return write!(f, "{c}"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Enter".to_owned(),
+            KeyCode::Backspace => "BS".to_owned(),
+            KeyCode::Delete => "Del".to_owned(),
+            KeyCode::F(n) => format!("F{n}"),
+            code => format!("{code:?}"),
+        };
+        let ctrl = if self.ctrl { "C-" } else { "" };
+        let alt = if self.alt { "A-" } else { "" };
+        write!(f, "<{ctrl}{alt}{name}>")
+    }
+}
+
+macro_rules! actions {
+    ($($action:ident => $description:literal,)*) => {
+        /// Everything a key sequence can be bound to.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum Action {
+            $($action,)*
+        }
+        impl Action {
+            const ALL: &[Action] = &[$(Action::$action,)*];
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Action::$action => stringify!($action),)*
+                }
+            }
+            pub fn description(&self) -> &'static str {
+                match self {
+                    $(Action::$action => $description,)*
+                }
+            }
+        }
+    };
+}
+actions! {
+    MoveUp => "Move up",
+    MoveDown => "Move down",
+    MoveLeft => "Move left",
+    MoveRight => "Move right",
+    MoveTop => "Go to the first row",
+    MoveBottom => "Go to the last row",
+    ScrollUp => "Scroll up",
+    ScrollDown => "Scroll down",
+    ClearCount => "Clear the count",
+    Back => "Go back",
+    Quit => "Save and quit",
+    ToggleActive => "Toggle whether the selected mods are active",
+    SortLoadOrder => "Sort the load order",
+    Rescan => "Rescan the mod folders",
+    CreateTag => "Create a tag",
+    ShowTags => "List tags",
+    TagMod => "Add a tag to the selected mod",
+    UntagMod => "Remove a tag from the selected mod",
+    RemoveTag => "Remove the highlighted tag",
+    EditTag => "Edit or rename the highlighted tag",
+    DeleteTag => "Delete the highlighted tag from every mod",
+    InsertTag => "Add the highlighted tag",
+    SelectRange => "Start a range selection or keep the current one",
+    ToggleSelected => "Select or unselect the mod under the cursor",
+    ShiftUp => "Move the selected mods up the load order",
+    ShiftDown => "Move the selected mods down the load order",
+    Undo => "Undo the last change to the tags, profiles or load order",
+    Redo => "Redo the last undone change",
+    DetailsUp => "Scroll the mod details up",
+    DetailsDown => "Scroll the mod details down",
+    ToggleDetails => "Show or hide the mod details",
+    Search => "Search mods",
+    SearchNext => "Next search match",
+    SearchPrev => "Previous search match",
+    ConfirmSearch => "Keep the selected match",
+    CancelSearch => "Cancel the search",
+    Filter => "Filter mods by tags and metadata",
+    ClearFilter => "Show every mod",
+    ConfirmFilter => "Apply the filter",
+    CancelFilter => "Restore the previous filter",
+    ShowProfiles => "List profiles",
+    SwitchProfile => "Switch to the highlighted profile",
+    NewProfile => "Save the load order as a new profile",
+    DuplicateProfile => "Duplicate the highlighted profile",
+    RenameProfile => "Rename the highlighted profile",
+    DeleteProfile => "Delete the highlighted profile",
+    MarkProfile => "Mark the highlighted profile to compare against",
+    DiffProfile => "Compare the highlighted profile with the marked one or the load order",
+    ShowSaves => "List saves to import their mod list",
+    ImportSave => "Import the mod list of the highlighted save as a profile",
+    ShowExport => "Export the shown mods or the load order",
+    Export => "Export in the highlighted format",
+    ShowCycles => "Show load order cycles",
+    Validate => "Validate the load order",
+    JumpToIssue => "Jump to the offending mod",
+    ShowScanErrors => "Show scan errors",
+    Help => "Show this help",
+}
+impl Action {
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+    fn allowed_in(&self, mode: Mode) -> bool {
+        use Action::*;
+        let lists = matches!(
+            mode,
+            Mode::Normal
+                | Mode::Visual
+                | Mode::ShowTags
+                | Mode::Insert
+                | Mode::Issues
+                | Mode::Saves
+                | Mode::Export
+                | Mode::Profiles
+        );
+        let scrolls = matches!(
+            mode,
+            Mode::ShowCycles | Mode::ScanErrors | Mode::Help | Mode::Diff
+        );
+        match self {
+            MoveLeft | MoveRight | MoveTop | MoveBottom if mode == Mode::RemoveTag => true,
+            MoveUp | MoveDown | MoveLeft | MoveRight | MoveTop | MoveBottom => lists,
+            ScrollUp | ScrollDown => scrolls,
+            ClearCount => true,
+            Back => !matches!(mode, Mode::Normal),
+            InsertTag => matches!(mode, Mode::Insert),
+            RemoveTag => matches!(mode, Mode::RemoveTag | Mode::Insert),
+            EditTag | DeleteTag => matches!(mode, Mode::ShowTags),
+            JumpToIssue => matches!(mode, Mode::Issues),
+            ImportSave => matches!(mode, Mode::Saves),
+            Export => matches!(mode, Mode::Export),
+            ConfirmSearch | CancelSearch => matches!(mode, Mode::Search),
+            ConfirmFilter | CancelFilter => matches!(mode, Mode::Filter),
+            SwitchProfile | NewProfile | DuplicateProfile | RenameProfile | DeleteProfile
+            | MarkProfile | DiffProfile | ShowSaves => {
+                matches!(mode, Mode::Profiles)
+            }
+            ToggleActive | TagMod | SelectRange | ToggleSelected => {
+                matches!(mode, Mode::Normal | Mode::Visual)
+            }
+            ShiftUp | ShiftDown => matches!(mode, Mode::Visual),
+            Quit | SortLoadOrder | Rescan | CreateTag | ShowTags | UntagMod | Search
+            | SearchNext | SearchPrev | ShowCycles | Validate | ShowScanErrors | Help | Undo
+            | Redo | DetailsUp | DetailsDown | ToggleDetails | Filter | ClearFilter
+            | ShowProfiles | ShowExport => {
+                matches!(mode, Mode::Normal)
+            }
+        }
+    }
+    pub fn message(&self, mode: Mode) -> Message {
+        match self {
+            Action::MoveUp | Action::ScrollUp => Message::MoveDirection(MoveDirection::Up),
+            Action::MoveDown | Action::ScrollDown => Message::MoveDirection(MoveDirection::Down),
+            Action::MoveLeft => Message::MoveDirection(MoveDirection::Left),
+            Action::MoveRight => Message::MoveDirection(MoveDirection::Right),
+            Action::MoveTop => Message::MoveDirection(MoveDirection::Top),
+            Action::MoveBottom => Message::MoveDirection(MoveDirection::Bottom),
+            Action::ClearCount => Message::ClearCommand,
+            Action::Back | Action::ConfirmSearch => Message::ChangeMode(mode.parent()),
+            Action::Quit => Message::Exit,
+            Action::ToggleActive => Message::ToggleActive,
+            Action::SortLoadOrder => Message::SortLoadOrder,
+            Action::Rescan => Message::Rescan,
+            Action::CreateTag => Message::ChangeMode(Mode::CreateTag),
+            Action::ShowTags => Message::ChangeMode(Mode::ShowTags),
+            Action::TagMod => Message::ChangeMode(Mode::Insert),
+            Action::UntagMod => Message::ChangeMode(Mode::RemoveTag),
+            Action::RemoveTag => Message::RemoveTag,
+            Action::InsertTag => Message::InsertTag,
+            Action::EditTag => Message::ChangeMode(Mode::EditTag),
+            Action::DeleteTag => Message::DeleteTag,
+            Action::SelectRange => Message::SelectRange,
+            Action::ToggleSelected => Message::ToggleSelected,
+            Action::ShiftUp => Message::ShiftLoadOrder(true),
+            Action::ShiftDown => Message::ShiftLoadOrder(false),
+            Action::Undo => Message::Undo,
+            Action::Redo => Message::Redo,
+            Action::DetailsUp => Message::ScrollDetails(MoveDirection::Up),
+            Action::DetailsDown => Message::ScrollDetails(MoveDirection::Down),
+            Action::ToggleDetails => Message::ToggleDetails,
+            Action::Search => Message::ChangeMode(Mode::Search),
+            Action::SearchNext => Message::SearchNext(true),
+            Action::SearchPrev => Message::SearchNext(false),
+            Action::CancelSearch => Message::CancelSearch,
+            Action::Filter => Message::ChangeMode(Mode::Filter),
+            Action::ClearFilter => Message::ClearFilter,
+            Action::ConfirmFilter => Message::ConfirmFilter,
+            Action::CancelFilter => Message::CancelFilter,
+            Action::ShowProfiles => Message::ChangeMode(Mode::Profiles),
+            Action::SwitchProfile => Message::SwitchProfile,
+            Action::NewProfile => Message::NameProfile(ProfileEdit::New),
+            Action::DuplicateProfile => Message::NameProfile(ProfileEdit::Duplicate),
+            Action::RenameProfile => Message::NameProfile(ProfileEdit::Rename),
+            Action::DeleteProfile => Message::DeleteProfile,
+            Action::MarkProfile => Message::MarkProfile,
+            Action::DiffProfile => Message::DiffProfile,
+            Action::ShowSaves => Message::ChangeMode(Mode::Saves),
+            Action::ImportSave => Message::ImportSave,
+            Action::ShowExport => Message::ChangeMode(Mode::Export),
+            Action::Export => Message::Export,
+            Action::ShowCycles => Message::ChangeMode(Mode::ShowCycles),
+            Action::Validate => Message::ChangeMode(Mode::Issues),
+            Action::JumpToIssue => Message::JumpToIssue,
+            Action::ShowScanErrors => Message::ChangeMode(Mode::ScanErrors),
+            Action::Help => Message::ChangeMode(Mode::Help),
+        }
+    }
+}
+
+/// Bindings of `mode` used unless overridden.
+pub fn defaults(mode: Mode) -> Vec<(&'static str, Action)> {
+    use Action::*;
+    const LIST: [(&str, Action); 6] = [
+        ("j", MoveDown),
+        ("<Down>", MoveDown),
+        ("k", MoveUp),
+        ("<Up>", MoveUp),
+        ("gg", MoveTop),
+        ("G", MoveBottom),
+    ];
+    const SIDEWAYS: [(&str, Action); 4] = [
+        ("h", MoveLeft),
+        ("<Left>", MoveLeft),
+        ("l", MoveRight),
+        ("<Right>", MoveRight),
+    ];
+    const BACK: [(&str, Action); 2] = [("q", Back), ("<Esc>", Back)];
+    match mode {
+        Mode::Normal => [
+            &LIST[..],
+            &SIDEWAYS,
+            &[
+                ("<Esc>", ClearCount),
+                ("a", ToggleActive),
+                ("s", SortLoadOrder),
+                ("r", Rescan),
+                ("c", CreateTag),
+                ("t", ShowTags),
+                ("i", TagMod),
+                ("x", UntagMod),
+                ("v", SelectRange),
+                ("V", ToggleSelected),
+                ("u", Undo),
+                ("<C-r>", Redo),
+                ("<C-y>", DetailsUp),
+                ("<C-e>", DetailsDown),
+                ("<Tab>", ToggleDetails),
+                ("/", Search),
+                ("n", SearchNext),
+                ("N", SearchPrev),
+                ("f", Filter),
+                ("F", ClearFilter),
+                ("P", ShowProfiles),
+                ("e", ShowExport),
+                ("C", ShowCycles),
+                ("!", Validate),
+                ("E", ShowScanErrors),
+                ("?", Help),
+                ("q", Quit),
+            ],
+        ]
+        .concat(),
+        Mode::Visual => [
+            &LIST[..],
+            &[
+                ("v", SelectRange),
+                ("V", ToggleSelected),
+                ("<Space>", ToggleSelected),
+                ("i", TagMod),
+                ("a", ToggleActive),
+                ("K", ShiftUp),
+                ("J", ShiftDown),
+            ],
+            &BACK,
+        ]
+        .concat(),
+        Mode::CreateTag | Mode::EditTag | Mode::NameProfile => vec![("<Esc>", Back)],
+        Mode::Profiles => [
+            &LIST[..],
+            &[
+                ("<Enter>", SwitchProfile),
+                ("n", NewProfile),
+                ("c", DuplicateProfile),
+                ("r", RenameProfile),
+                ("dd", DeleteProfile),
+                ("m", MarkProfile),
+                ("D", DiffProfile),
+                ("S", ShowSaves),
+            ],
+            &BACK,
+        ]
+        .concat(),
+        Mode::ShowTags => [&LIST[..], &[("e", EditTag), ("dd", DeleteTag)], &BACK].concat(),
+        Mode::RemoveTag => [
+            &SIDEWAYS[..],
+            &LIST[4..],
+            &[("<Enter>", RemoveTag), ("x", RemoveTag)],
+            &BACK,
+        ]
+        .concat(),
+        Mode::Insert => [
+            &LIST[..],
+            &SIDEWAYS,
+            &[("<Enter>", InsertTag), ("x", RemoveTag)],
+            &BACK,
+        ]
+        .concat(),
+        Mode::Issues => [&LIST[..], &[("<Enter>", JumpToIssue)], &BACK].concat(),
+        Mode::Saves => [&LIST[..], &[("<Enter>", ImportSave)], &BACK].concat(),
+        Mode::Export => [&LIST[..], &[("<Enter>", Export)], &BACK].concat(),
+        Mode::ShowCycles | Mode::ScanErrors | Mode::Help | Mode::Diff => [
+            &[
+                ("j", ScrollDown),
+                ("<Down>", ScrollDown),
+                ("k", ScrollUp),
+                ("<Up>", ScrollUp),
+            ][..],
+            &BACK,
+        ]
+        .concat(),
+        Mode::Search => vec![("<Enter>", ConfirmSearch), ("<Esc>", CancelSearch)],
+        Mode::Filter => vec![("<Enter>", ConfirmFilter), ("<Esc>", CancelFilter)],
+    }
+}
+
+pub struct Binding {
+    pub keys: Vec<Key>,
+    /// How the sequence was written.
+    pub repr: String,
+    pub action: Action,
+}
+pub enum Resolution {
+    Action(Action),
+    /// The keys are the start of a longer sequence.
+    Pending,
+    None,
+}
+
+#[derive(Debug)]
+pub struct KeymapError {
+    pub mode: Mode,
+    pub keys: String,
+    pub reason: String,
+}
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}`{}`: {}",
+            self.mode.str_repr().trim_start(),
+            self.keys,
+            self.reason
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct Keymap {
+    modes: HashMap<Mode, Vec<Binding>>,
+}
+impl Keymap {
+    /// [`defaults`] with `overrides` applied, every invalid binding is
+    /// reported at once.
+    pub fn new(
+        overrides: &HashMap<Mode, HashMap<String, String>>,
+    ) -> Result<Self, Vec<KeymapError>> {
+        let mut res = Self::default();
+        let mut errors = vec![];
+        for mode in Mode::ALL {
+            for (keys, action) in defaults(mode) {
+                res.bind(mode, keys, action)
+                    .expect("default bindings should be valid");
+            }
+        }
+
+        let mut overrides: Vec<_> = overrides.iter().collect();
+        overrides.sort_by_key(|(mode, _)| Mode::ALL.iter().position(|m| m == *mode));
+        for (&mode, bindings) in overrides {
+            let mut bindings: Vec<_> = bindings.iter().collect();
+            bindings.sort();
+            for (keys, action) in bindings {
+                let error = |reason: String| KeymapError {
+                    mode,
+                    keys: keys.clone(),
+                    reason,
+                };
+                if action == "Unbind" {
+                    if let Err(reason) = res.unbind(mode, keys) {
+                        errors.push(error(reason));
+                    }
+                    continue;
+                }
+                let Some(action) = Action::from_name(action) else {
+                    errors.push(error(format!("unknown action `{action}`")));
+                    continue;
+                };
+                if !action.allowed_in(mode) {
+                    errors.push(error(format!(
+                        "`{}` can't be used in this mode",
+                        action.name()
+                    )));
+                    continue;
+                }
+                if let Err(reason) = res.bind(mode, keys, action) {
+                    errors.push(error(reason));
+                }
+            }
+        }
+
+        for mode in Mode::ALL {
+            for binding in res.bindings(mode) {
+                if let Some(longer) = res.bindings(mode).iter().find(|b| {
+                    b.keys.len() > binding.keys.len() && b.keys.starts_with(&binding.keys)
+                }) {
+                    errors.push(KeymapError {
+                        mode,
+                        keys: binding.repr.clone(),
+                        reason: format!("shadows `{}`", longer.repr),
+                    });
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(res)
+        } else {
+            Err(errors)
+        }
+    }
+    fn bind(&mut self, mode: Mode, repr: &str, action: Action) -> Result<(), String> {
+        let keys = Key::parse_sequence(repr)?;
+        let bindings = self.modes.entry(mode).or_default();
+        bindings.retain(|b| b.keys != keys);
+        bindings.push(Binding {
+            keys,
+            repr: repr.to_owned(),
+            action,
+        });
+        Ok(())
+    }
+    fn unbind(&mut self, mode: Mode, repr: &str) -> Result<(), String> {
+        let keys = Key::parse_sequence(repr)?;
+        if let Some(bindings) = self.modes.get_mut(&mode) {
+            bindings.retain(|b| b.keys != keys);
+        }
+        Ok(())
+    }
+    pub fn bindings(&self, mode: Mode) -> &[Binding] {
+        self.modes.get(&mode).map_or(&[], Vec::as_slice)
+    }
+    /// How the first sequence bound to `action` in `mode` was written.
+    pub fn first_key(&self, mode: Mode, action: Action) -> Option<&str> {
+        self.bindings(mode)
+            .iter()
+            .find(|b| b.action == action)
+            .map(|b| b.repr.as_str())
+    }
+    /// Key sequences and what they do in `mode`, grouped by action.
+    pub fn help(&self, mode: Mode) -> Vec<(String, &'static str)> {
+        let mut res: Vec<(String, &'static str)> = vec![];
+        for binding in self.bindings(mode) {
+            let description = binding.action.description();
+            match res.iter_mut().find(|(_, d)| *d == description) {
+                Some((keys, _)) => {
+                    keys.push_str(", ");
+                    keys.push_str(&binding.repr);
+                }
+                None => res.push((binding.repr.clone(), description)),
+            }
+        }
+        match mode {
+            Mode::CreateTag | Mode::EditTag => {
+                res.push((
+                    "<Tab>, <Enter>".to_owned(),
+                    "Next field, confirm on the last one",
+                ));
+            }
+            Mode::Search | Mode::Filter => res.push(("Any".to_owned(), "Edit the query")),
+            Mode::NameProfile => res.push(("<Enter>".to_owned(), "Save the name")),
+            _ => res.push(("0-9".to_owned(), "Repeat the next action")),
+        }
+        res
+    }
+    pub fn resolve(&self, mode: Mode, keys: &[Key]) -> Resolution {
+        let mut res = Resolution::None;
+        for binding in self.bindings(mode) {
+            if binding.keys == keys {
+                return Resolution::Action(binding.action);
+            }
+            if binding.keys.starts_with(keys) {
+                res = Resolution::Pending;
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(mode: Mode, bindings: &[(&str, &str)]) -> Result<Keymap, Vec<String>> {
+        let bindings = bindings
+            .iter()
+            .map(|(keys, action)| (keys.to_string(), action.to_string()))
+            .collect();
+        Keymap::new(&HashMap::from([(mode, bindings)]))
+            .map_err(|errors| errors.iter().map(ToString::to_string).collect())
+    }
+    fn resolves_to(keymap: &Keymap, mode: Mode, keys: &str) -> Option<&'static str> {
+        match keymap.resolve(mode, &Key::parse_sequence(keys).unwrap()) {
+            Resolution::Action(action) => Some(action.name()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Keymap::new(&Default::default()).is_ok());
+    }
+
+    #[test]
+    fn overrides_replace_and_unbind() {
+        let keymap = keymap(Mode::Normal, &[("<C-d>", "MoveBottom"), ("q", "Unbind")]).unwrap();
+        assert_eq!(
+            resolves_to(&keymap, Mode::Normal, "<C-d>"),
+            Some("MoveBottom")
+        );
+        assert_eq!(resolves_to(&keymap, Mode::Normal, "q"), None);
+        assert_eq!(resolves_to(&keymap, Mode::Normal, "gg"), Some("MoveTop"));
+    }
+
+    #[test]
+    fn shadowing_sequences_are_rejected() {
+        assert_eq!(
+            keymap(Mode::Normal, &[("g", "Help")]).err().unwrap(),
+            ["NORMAL `g`: shadows `gg`"]
+        );
+    }
+
+    #[test]
+    fn invalid_overrides_are_all_reported() {
+        assert_eq!(
+            keymap(
+                Mode::Normal,
+                &[
+                    ("<Hyper-x>", "Quit"),
+                    ("x", "DeleteProfile"),
+                    ("y", "Frobnicate"),
+                ]
+            )
+            .err()
+            .unwrap(),
+            [
+                "NORMAL `<Hyper-x>`: unknown modifier `Hyper`",
+                "NORMAL `x`: `DeleteProfile` can't be used in this mode",
+                "NORMAL `y`: unknown action `Frobnicate`",
+            ]
+        );
+    }
+}
@@ -1,82 +1,145 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{Event, KeyEventKind};
 
-use super::{Mode, Model};
+use super::{
+    Mode, Model,
+    keymap::{Key, Resolution},
+};
+use crate::mods::Tag;
 
 pub enum MoveDirection {
     Up,
     Down,
     Left,
     Right,
+    Top,
+    Bottom,
+}
+impl MoveDirection {
+    /// Row reached from `selected` in a list of `len` rows, `count` is how
+    /// many rows to move or, going to the top or bottom, the row to go to.
+    pub fn apply(&self, selected: usize, count: Option<usize>, len: usize) -> usize {
+        let last = len.saturating_sub(1);
+        let d = count.unwrap_or(1);
+        match self {
+            MoveDirection::Up | MoveDirection::Left => selected.saturating_sub(d),
+            MoveDirection::Down | MoveDirection::Right => selected.saturating_add(d).min(last),
+            MoveDirection::Top => count.unwrap_or(0).min(last),
+            MoveDirection::Bottom => count.unwrap_or(last).min(last),
+        }
+    }
+}
+/// What to do with the name typed in [`Mode::NameProfile`].
+#[derive(Clone, Copy, Debug)]
+pub enum ProfileEdit {
+    /// Saves the load order as a new profile.
+    New,
+    Duplicate,
+    Rename,
 }
 pub enum Message {
     ClearCommand,
     AppendMovement(char),
+    /// Keys typed so far of a longer sequence.
+    PendingKeys(Vec<Key>),
     MoveDirection(MoveDirection),
     PropagateEvent(Event),
     InsertTag,
+    /// Result of the tag form, replaces the tag being edited if any.
+    SaveTag(Tag),
+    DeleteTag,
+    /// Removes the highlighted tag from the selected mods.
+    RemoveTag,
+    SelectRange,
+    ToggleSelected,
+    /// Moves the selected mods in the load order, `true` to move them up.
+    ShiftLoadOrder(bool),
+    ToggleActive,
+    SortLoadOrder,
+    Rescan,
+    CancelSearch,
+    ConfirmFilter,
+    /// Restores the filter from before it was edited.
+    CancelFilter,
+    ClearFilter,
+    /// `true` to search forward
+    SearchNext(bool),
+    JumpToIssue,
+    Undo,
+    Redo,
+    /// Saves the load order into the current profile and loads the
+    /// highlighted one.
+    SwitchProfile,
+    NameProfile(ProfileEdit),
+    SaveProfile(String),
+    DeleteProfile,
+    /// Toggles the highlighted profile as the base of [`Message::DiffProfile`].
+    MarkProfile,
+    /// Compares the highlighted profile with the marked one, or with the
+    /// load order if none is.
+    DiffProfile,
+    /// Turns the mod list of the highlighted save into a profile.
+    ImportSave,
+    /// Writes the shown mods in the highlighted format.
+    Export,
+    /// Only [`MoveDirection::Up`] and [`MoveDirection::Down`] are used.
+    ScrollDetails(MoveDirection),
+    ToggleDetails,
     ChangeMode(Mode),
     Exit,
 }
-pub fn try_message(model: &Model, ev: Event) -> Option<Message> {
-    match ev {
-        Event::Key(key_event) => match key_event.kind {
-            KeyEventKind::Press => match model.current_mode {
-                Mode::Normal => normal_key_press(key_event),
-                Mode::CreateTag => {
-                    let res = match key_event.code {
-                        KeyCode::Esc => Message::ChangeMode(Mode::Normal),
-                        _ => Message::PropagateEvent(ev),
-                    };
-                    Some(res)
-                }
-                Mode::ShowTags => show_tags_key_press(key_event),
-                Mode::Insert => insert_key_press(key_event),
-            },
-            KeyEventKind::Repeat => None,
-            KeyEventKind::Release => None,
-        },
-        Event::Mouse(_) => None,
-        _ => None,
+impl Message {
+    /// Whether handling it may change the tags, the profiles or the load
+    /// order, such changes can be undone, see [`super::history`].
+    ///
+    /// Rescans aren't, they only mirror the mods on disk and keep the tags.
+    pub fn is_undoable(&self) -> bool {
+        matches!(
+            self,
+            Message::SaveTag(_)
+                | Message::DeleteTag
+                | Message::InsertTag
+                | Message::RemoveTag
+                | Message::SortLoadOrder
+                | Message::ToggleActive
+                | Message::ShiftLoadOrder(_)
+                | Message::SwitchProfile
+                | Message::SaveProfile(_)
+                | Message::DeleteProfile
+                | Message::ImportSave
+        )
     }
 }
-
-#[inline]
-fn normal_key_press(key: KeyEvent) -> Option<Message> {
-    let res = match key.code {
-        KeyCode::Esc => Message::ClearCommand,
-        KeyCode::Char(c) if c.is_ascii_digit() => Message::AppendMovement(c),
-        KeyCode::Char('q') => Message::Exit,
-        KeyCode::Char('c') => Message::ChangeMode(Mode::CreateTag),
-        KeyCode::Char('t') => Message::ChangeMode(Mode::ShowTags),
-        KeyCode::Char('i') => Message::ChangeMode(Mode::Insert),
-        KeyCode::Char('?') => todo!(),
-        KeyCode::Char('k') | KeyCode::Up => Message::MoveDirection(MoveDirection::Up),
-        KeyCode::Char('j') | KeyCode::Down => Message::MoveDirection(MoveDirection::Down),
-        KeyCode::Char('h') | KeyCode::Left => Message::MoveDirection(MoveDirection::Left),
-        KeyCode::Char('l') | KeyCode::Right => Message::MoveDirection(MoveDirection::Right),
-        _ => return None,
-    };
-    Some(res)
-}
-#[inline]
-fn show_tags_key_press(key: KeyEvent) -> Option<Message> {
-    let res = match key.code {
-        KeyCode::Esc => Message::ChangeMode(Mode::Normal),
-        _ => return None,
+pub fn try_message(model: &Model, ev: Event) -> Option<Message> {
+    let Event::Key(key_event) = ev else {
+        return None;
     };
-    Some(res)
-}
-#[inline]
-fn insert_key_press(key: KeyEvent) -> Option<Message> {
-    let res = match key.code {
-        KeyCode::Esc | KeyCode::Char('q') => Message::ChangeMode(Mode::Normal),
-        KeyCode::Enter => Message::InsertTag,
-        KeyCode::Char(c) if c.is_ascii_digit() => Message::AppendMovement(c),
-        KeyCode::Char('k') | KeyCode::Up => Message::MoveDirection(MoveDirection::Up),
-        KeyCode::Char('j') | KeyCode::Down => Message::MoveDirection(MoveDirection::Down),
-        KeyCode::Char('h') | KeyCode::Left => Message::MoveDirection(MoveDirection::Left),
-        KeyCode::Char('l') | KeyCode::Right => Message::MoveDirection(MoveDirection::Right),
-        _ => return None,
+    if key_event.kind != KeyEventKind::Press {
+        return None;
+    }
+    let key = Key::from(key_event);
+    let mode = model.current_mode;
+
+    let mut candidates = vec![vec![key]];
+    if !model.pending_keys.is_empty() {
+        // a sequence that went nowhere is dropped, the key may still start
+        // another one
+        candidates.insert(0, [model.pending_keys.as_slice(), &[key]].concat());
+    }
+    for keys in candidates {
+        match model.keymap.resolve(mode, &keys) {
+            Resolution::Action(action) => return Some(action.message(mode)),
+            Resolution::Pending => return Some(Message::PendingKeys(keys)),
+            Resolution::None => {}
+        }
+    }
+    let res = match mode {
+        Mode::CreateTag | Mode::EditTag | Mode::Search | Mode::Filter | Mode::NameProfile => {
+            Some(Message::PropagateEvent(ev))
+        }
+        _ => key.digit().map(Message::AppendMovement),
     };
-    Some(res)
+    match res {
+        None if !model.pending_keys.is_empty() => Some(Message::PendingKeys(vec![])),
+        res => res,
+    }
 }
@@ -0,0 +1,114 @@
+//! Older `mod_info.ron` layouts
+//!
+//! Mods used to embed a copy of each of their tags, the copies are added to
+//! the registry unless it already has a tag with that name.
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use super::Persistent;
+use crate::mods::{Item, Mod, OrderedItems, Tag, game::ModMetaData, scan::Source};
+
+/// How [`OrderedItems`] is saved.
+#[derive(Deserialize)]
+struct Items<T> {
+    data: Vec<T>,
+}
+#[derive(Deserialize)]
+struct LegacyMod {
+    metadata: ModMetaData,
+    tags: Items<Tag>,
+    #[serde(default)]
+    source: Source,
+    #[serde(default)]
+    path: PathBuf,
+    #[serde(default)]
+    missing: bool,
+}
+#[derive(Deserialize)]
+pub struct LegacyPersistent {
+    mods: Items<LegacyMod>,
+    tags: OrderedItems<Tag>,
+}
+impl From<LegacyPersistent> for Persistent {
+    fn from(legacy: LegacyPersistent) -> Self {
+        let LegacyPersistent { mods, mut tags } = legacy;
+        let mods: Vec<Mod> = mods
+            .data
+            .into_iter()
+            .map(|m| {
+                for tag in &m.tags.data {
+                    if tags.get_by_name(tag.identifier()).is_none() {
+                        tags.upsert(tag.clone());
+                    }
+                }
+                Mod::from_legacy(m.metadata, &m.tags.data, m.source, m.path, m.missing)
+            })
+            .collect();
+        Self {
+            mods: mods.into(),
+            tags,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two mods sharing a tag the registry lost, and a copy of a registered
+    /// tag whose score is out of date.
+    const LEGACY: &str = r#"(
+    mods: (
+        data: [
+            (
+                metadata: (name: "A", packageId: "a.a"),
+                tags: (
+                    data: [
+                        (name: "favorite", score: 1, color: "Red"),
+                        (name: "lost", score: 2, color: "Blue"),
+                    ],
+                ),
+                source: Local,
+                path: "Mods/a",
+                missing: false,
+            ),
+            (
+                metadata: (name: "B", packageId: "b.b"),
+                tags: (data: [(name: "lost", score: 2, color: "Blue")]),
+                missing: true,
+            ),
+        ],
+    ),
+    tags: (data: [(name: "favorite", score: 5, color: "Green")]),
+)"#;
+
+    #[test]
+    fn embedded_tags_become_references() {
+        let persistent = Persistent::from_ron(LEGACY).unwrap();
+        let tags: Vec<(&str, u64)> = persistent
+            .tags
+            .iter()
+            .map(|t| (t.name.as_str(), t.score))
+            .collect();
+        assert_eq!(tags, [("lost", 2), ("favorite", 5)]);
+
+        let a = persistent.mods.get_by_name("a.a").unwrap();
+        let names: Vec<&str> = a.tags().iter().map(|t| t.identifier()).collect();
+        assert_eq!(names, ["lost", "favorite"]);
+        assert_eq!(a.path, PathBuf::from("Mods/a"));
+        let b = persistent.mods.get_by_name("b.b").unwrap();
+        assert!(b.has_tag("lost"));
+        assert!(b.missing);
+        assert!(persistent.profiles.is_empty());
+
+        let again = Persistent::from_ron(&persistent.to_ron().unwrap()).unwrap();
+        assert_eq!(again.to_ron().unwrap(), persistent.to_ron().unwrap());
+    }
+
+    #[test]
+    fn unreadable_files_are_errors() {
+        assert!(Persistent::from_ron("(mods: (data: [").is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Unity rich text, used by mod descriptions
+//!
+//! `<b>`, `<i>` and `<color>` become styles, `<size>` has no terminal
+//! equivalent and is dropped. Anything else is kept as written, the same
+//! way the game shows it.
+use std::str::FromStr as _;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+enum RichTag {
+    Bold,
+    Italic,
+    Color(Color),
+    Size,
+}
+impl RichTag {
+    /// The tag opened by `<{text}>`.
+    fn parse(text: &str) -> Option<Self> {
+        let (name, value) = match text.split_once('=') {
+            Some((name, value)) => (name, Some(value.trim_matches('"'))),
+            None => (text, None),
+        };
+        match (name.to_ascii_lowercase().as_str(), value) {
+            ("b", None) => Some(RichTag::Bold),
+            ("i", None) => Some(RichTag::Italic),
+            ("size", Some(_)) => Some(RichTag::Size),
+            ("color", Some(value)) => {
+                // `#rrggbbaa`, the alpha is ignored
+                let value = match value.len() {
+                    9 if value.starts_with('#') => value.get(..7).unwrap_or(value),
+                    _ => value,
+                };
+                Color::from_str(value).ok().map(RichTag::Color)
+            }
+            _ => None,
+        }
+    }
+    fn name(&self) -> &'static str {
+        match self {
+            RichTag::Bold => "b",
+            RichTag::Italic => "i",
+            RichTag::Color(_) => "color",
+            RichTag::Size => "size",
+        }
+    }
+    fn apply(&self, style: Style) -> Style {
+        match self {
+            RichTag::Bold => style.add_modifier(Modifier::BOLD),
+            RichTag::Italic => style.add_modifier(Modifier::ITALIC),
+            RichTag::Color(color) => style.fg(*color),
+            RichTag::Size => style,
+        }
+    }
+}
+
+/// `text` split in lines styled after its rich text tags.
+pub fn to_lines(text: &str, base: Style) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    let mut spans = vec![];
+    let mut stack: Vec<RichTag> = vec![];
+    let mut buff = String::new();
+    let style = |stack: &[RichTag]| stack.iter().fold(base, |style, tag| tag.apply(style));
+
+    let mut rest = text;
+    while !rest.is_empty() {
+        let next = rest.find(['<', '\n']).unwrap_or(rest.len());
+        buff.push_str(rest[..next].trim_end_matches('\r'));
+        rest = &rest[next..];
+        if let Some(after) = rest.strip_prefix('\n') {
+            spans.push(Span::styled(std::mem::take(&mut buff), style(&stack)));
+            lines.push(Line::from(std::mem::take(&mut spans)));
+            rest = after;
+            continue;
+        }
+        if rest.is_empty() {
+            break;
+        }
+        let end = rest[1..].find(['<', '>', '\n']).map(|idx| idx + 1);
+        let Some(end) = end.filter(|&end| rest[end..].starts_with('>')) else {
+            // a lone `<`
+            buff.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+        let inner = &rest[1..end];
+        let known = if let Some(name) = inner.strip_prefix('/') {
+            stack
+                .iter()
+                .rposition(|tag| tag.name().eq_ignore_ascii_case(name))
+                .map(|idx| (idx, None))
+        } else {
+            RichTag::parse(inner).map(|tag| (stack.len(), Some(tag)))
+        };
+        match known {
+            Some((idx, tag)) => {
+                if !buff.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut buff), style(&stack)));
+                }
+                match tag {
+                    Some(tag) => stack.push(tag),
+                    None => stack.truncate(idx),
+                }
+            }
+            None => buff.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    if !buff.is_empty() {
+        spans.push(Span::styled(buff, style(&stack)));
+    }
+    if !spans.is_empty() {
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Stylize as _;
+
+    use super::*;
+
+    /// Text and style of every span, line by line.
+    fn spans(text: &str) -> Vec<Vec<(String, Style)>> {
+        to_lines(text, Style::default())
+            .into_iter()
+            .map(|line| {
+                line.spans
+                    .into_iter()
+                    .map(|span| (span.content.into_owned(), span.style))
+                    .collect()
+            })
+            .collect()
+    }
+    fn span(text: &str, style: Style) -> (String, Style) {
+        (text.to_owned(), style)
+    }
+
+    #[test]
+    fn nested_tags_combine() {
+        assert_eq!(
+            spans("a<b>b<i>c</i></b><color=red>d</color>"),
+            [[
+                span("a", Style::default()),
+                span("b", Style::default().bold()),
+                span("c", Style::default().bold().italic()),
+                span("d", Style::default().fg(Color::Red)),
+            ]]
+        );
+    }
+
+    #[test]
+    fn closing_an_outer_tag_closes_the_inner_ones() {
+        assert_eq!(
+            spans("<b><i>a</b>b"),
+            [[
+                span("a", Style::default().bold().italic()),
+                span("b", Style::default()),
+            ]]
+        );
+    }
+
+    #[test]
+    fn unclosed_tags_last_until_the_end() {
+        assert_eq!(
+            spans("<b>a\nb</i> 1 < 2"),
+            [
+                vec![span("a", Style::default().bold())],
+                vec![span("b</i> 1 < 2", Style::default().bold())],
+            ]
+        );
+    }
+
+    #[test]
+    fn alpha_is_ignored() {
+        assert_eq!(
+            spans("<color=#ff000080>a</color><color=\"#00ff00\">b</color>"),
+            [[
+                span("a", Style::default().fg(Color::Rgb(0xff, 0, 0))),
+                span("b", Style::default().fg(Color::Rgb(0, 0xff, 0))),
+            ]]
+        );
+    }
+
+    #[test]
+    fn unknown_tags_are_kept() {
+        assert_eq!(
+            spans("<color=#abcde€>a<size=20>b</size><u>c</u>"),
+            [[
+                span("<color=#abcde€>a", Style::default()),
+                span("b", Style::default()),
+                span("<u>c</u>", Style::default()),
+            ]]
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Fuzzy search over the mod table
+//!
+//! A query matches a mod if its characters appear in order, ignoring case,
+//! in the mod's name, packageId or one of its authors.
+use ratatui::{
+    style::{Color, Style, Stylize as _},
+    text::{Line, Span},
+};
+use tui_input::Input;
+
+use crate::mods::{Item, Mod};
+
+#[derive(Default)]
+pub struct Search {
+    pub input: Input,
+    /// Selected row when the search started.
+    pub origin: Option<usize>,
+    /// Rows matching the query, in table order.
+    matches: Vec<usize>,
+    best: Option<usize>,
+}
+impl Search {
+    pub fn query(&self) -> &str {
+        self.input.value()
+    }
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+    /// Row with the highest score, the first one wins ties.
+    pub fn best_match(&self) -> Option<usize> {
+        self.best
+    }
+    /// Looks for the query in the `rows` of the table.
+    pub fn update_matches<'a>(&mut self, rows: impl Iterator<Item = &'a Mod>) {
+        self.matches.clear();
+        self.best = None;
+        let mut best_score = i64::MIN;
+        for (idx, game_mod) in rows.enumerate() {
+            let metadata = &game_mod.metadata;
+            let score = [metadata.name.as_str(), game_mod.identifier()]
+                .into_iter()
+                .chain(metadata.author_names())
+                .filter_map(|field| fuzzy_match(self.query(), field))
+                .map(|(score, _)| score)
+                .max();
+            if let Some(score) = score {
+                self.matches.push(idx);
+                if score > best_score {
+                    best_score = score;
+                    self.best = Some(idx);
+                }
+            }
+        }
+    }
+    /// The `count`th match after `from` going forward or backwards, wrapping
+    /// around the table.
+    pub fn next_match(&self, from: usize, forward: bool, count: usize) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let len = self.matches.len();
+        // whole laps around the table don't move, only the rest does
+        let steps = (count.max(1) - 1) % len;
+        let pos = if forward {
+            let first_after = self.matches.partition_point(|&m| m <= from);
+            (first_after + steps) % len
+        } else {
+            let last_before = self.matches.partition_point(|&m| m < from);
+            (last_before + len - 1 - steps) % len
+        };
+        self.matches.get(pos).copied()
+    }
+    /// `name` with the characters matching the query highlighted.
+    pub fn highlight(&self, name: &str, color: Color) -> Line<'static> {
+        let Some((_, positions)) = fuzzy_match(self.query(), name) else {
+            return Line::from(name.to_owned());
+        };
+        let spans: Vec<Span> = name
+            .chars()
+            .enumerate()
+            .map(|(idx, ch)| {
+                if positions.contains(&idx) {
+                    Span::styled(ch.to_string(), Style::default().bold().fg(color))
+                } else {
+                    Span::raw(ch.to_string())
+                }
+            })
+            .collect();
+        Line::from(spans)
+    }
+}
+
+/// Score and char positions of `pattern` inside `text`, `None` if it doesn't
+/// match or `pattern` is empty.
+///
+/// Consecutive characters and characters starting a word score higher.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let mut pattern = pattern.chars().flat_map(char::to_lowercase).peekable();
+    pattern.peek()?;
+
+    let mut score = 0;
+    let mut positions = vec![];
+    let mut prev: Option<char> = None;
+    for (idx, ch) in text.chars().enumerate() {
+        let Some(&wanted) = pattern.peek() else {
+            break;
+        };
+        if ch.to_lowercase().eq(std::iter::once(wanted)) {
+            score += 1;
+            if positions.last().is_some_and(|&last| last + 1 == idx) {
+                score += 5;
+            }
+            if prev.is_none_or(|p| !p.is_alphanumeric() || (p.is_lowercase() && ch.is_uppercase()))
+            {
+                score += 3;
+            }
+            positions.push(idx);
+            pattern.next();
+        } else if !positions.is_empty() {
+            score -= 1;
+        }
+        prev = Some(ch);
+    }
+    match pattern.peek() {
+        Some(_) => None,
+        None => Some((score, positions)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(matches: &[usize]) -> Search {
+        Search {
+            matches: matches.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn next_match_wraps_around() {
+        let search = search(&[2, 5, 9]);
+        assert_eq!(search.next_match(5, true, 1), Some(9));
+        assert_eq!(search.next_match(5, true, 2), Some(2));
+        assert_eq!(search.next_match(5, false, 1), Some(2));
+        assert_eq!(search.next_match(5, false, 2), Some(9));
+        assert_eq!(search.next_match(0, false, 0), Some(9));
+        assert_eq!(Search::default().next_match(0, true, 1), None);
+    }
+
+    #[test]
+    fn huge_counts_do_not_overflow() {
+        let search = search(&[2, 5, 9]);
+        // usize::MAX is a multiple of 3, one step short of whole laps
+        assert_eq!(search.next_match(5, true, usize::MAX), Some(5));
+        assert_eq!(search.next_match(5, false, usize::MAX), Some(5));
+    }
+}
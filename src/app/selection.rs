@@ -0,0 +1,63 @@
+//! Mods picked in [`Mode::Visual`](super::Mode::Visual)
+//!
+//! The selection is made of the rows toggled one by one plus the range
+//! going from the anchor to the cursor over the rows shown. Rows are kept
+//! by packageId as tagging re-sorts the table.
+use std::collections::HashSet;
+
+use crate::mods::{Item, Mod};
+
+#[derive(Default)]
+pub struct Selection {
+    marked: HashSet<String>,
+    anchor: Option<String>,
+}
+impl Selection {
+    pub fn is_empty(&self) -> bool {
+        self.marked.is_empty() && self.anchor.is_none()
+    }
+    pub fn clear(&mut self) {
+        self.marked.clear();
+        self.anchor = None;
+    }
+    /// Starts a range at `cursor`, or keeps the current one and stops
+    /// following the cursor.
+    pub fn toggle_range<'a>(
+        &mut self,
+        mut rows: impl Iterator<Item = &'a Mod> + Clone,
+        cursor: usize,
+    ) {
+        if self.anchor.is_some() {
+            self.marked = self.package_ids(rows, Some(cursor));
+            self.anchor = None;
+        } else {
+            self.anchor = rows.nth(cursor).map(|m| m.identifier().to_owned());
+        }
+    }
+    pub fn toggle(&mut self, package_id: &str) {
+        if !self.marked.remove(package_id) {
+            self.marked.insert(package_id.to_owned());
+        }
+    }
+    /// Selected packageIds with the cursor at the row `cursor` of `rows`.
+    pub fn package_ids<'a>(
+        &self,
+        rows: impl Iterator<Item = &'a Mod> + Clone,
+        cursor: Option<usize>,
+    ) -> HashSet<String> {
+        let mut res = self.marked.clone();
+        let anchor = self
+            .anchor
+            .as_ref()
+            .and_then(|id| rows.clone().position(|m| m.identifier() == id));
+        if let (Some(anchor), Some(cursor)) = (anchor, cursor) {
+            let (first, last) = (anchor.min(cursor), anchor.max(cursor));
+            res.extend(
+                rows.skip(first)
+                    .take(last - first + 1)
+                    .map(|m| m.identifier().to_owned()),
+            );
+        }
+        res
+    }
+}
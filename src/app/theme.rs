@@ -0,0 +1,162 @@
+//! Colors used across the UI
+//!
+//! A [`Theme`] is picked from [`BuiltinTheme`] and every color may be
+//! overridden from `config.ron`, e.g.
+//! `theme: (base: Light, popup: "#e0e0e0", modes: {Normal: "#0043ce"})`.
+use std::collections::HashMap;
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use super::Mode;
+
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// Background of the mod table.
+    pub background: Color,
+    /// Background of popups and forms.
+    pub popup: Color,
+    pub status_line: Color,
+    /// Background of the selected row.
+    pub selection: Color,
+    /// Background of the rows picked in [`Mode::Visual`].
+    pub marked: Color,
+    pub text: Color,
+    /// Text of missing mods and placeholders.
+    pub muted: Color,
+    /// Notifications shown in the status line.
+    pub notification: Color,
+    modes: HashMap<Mode, Color>,
+}
+impl Default for Theme {
+    fn default() -> Self {
+        BuiltinTheme::default().theme()
+    }
+}
+impl Theme {
+    /// Color identifying `mode`, also used to highlight what it lists.
+    pub fn mode(&self, mode: Mode) -> Color {
+        self.modes.get(&mode).copied().unwrap_or(self.text)
+    }
+}
+
+/// Themes shipped with the app, colors are taken from IBM's Carbon palette.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub enum BuiltinTheme {
+    #[default]
+    Dark,
+    Light,
+}
+impl BuiltinTheme {
+    pub fn theme(&self) -> Theme {
+        match self {
+            BuiltinTheme::Dark => Theme {
+                background: Color::Rgb(0x16, 0x16, 0x16),
+                popup: Color::Rgb(0x26, 0x26, 0x26),
+                status_line: Color::Rgb(0x0b, 0x0b, 0x0b),
+                selection: Color::Rgb(0x39, 0x39, 0x39),
+                marked: Color::Rgb(0x49, 0x1d, 0x8b),
+                text: Color::Rgb(0xf4, 0xf4, 0xf4),
+                muted: Color::Rgb(0x8d, 0x8d, 0x8d),
+                notification: Color::Rgb(0xf4, 0xf4, 0xf4),
+                modes: HashMap::from([
+                    (Mode::Normal, Color::Rgb(0x45, 0x89, 0xff)),
+                    (Mode::Visual, Color::Rgb(0xbe, 0x95, 0xff)),
+                    (Mode::CreateTag, Color::Rgb(0x42, 0xbe, 0x65)),
+                    (Mode::ShowTags, Color::Rgb(0xfe, 0x83, 0x2b)),
+                    (Mode::EditTag, Color::Rgb(0x42, 0xbe, 0x65)),
+                    (Mode::Insert, Color::Rgb(0x42, 0xbe, 0x65)),
+                    (Mode::RemoveTag, Color::Rgb(0xff, 0x83, 0x89)),
+                    (Mode::ShowCycles, Color::Rgb(0xfa, 0x4d, 0x56)),
+                    (Mode::Issues, Color::Rgb(0xf1, 0xc2, 0x1b)),
+                    (Mode::ScanErrors, Color::Rgb(0xee, 0x53, 0x96)),
+                    (Mode::Search, Color::Rgb(0x08, 0xbd, 0xba)),
+                    (Mode::Filter, Color::Rgb(0x33, 0xb1, 0xff)),
+                    (Mode::Profiles, Color::Rgb(0x6f, 0xdc, 0x8c)),
+                    (Mode::NameProfile, Color::Rgb(0x6f, 0xdc, 0x8c)),
+                    (Mode::Diff, Color::Rgb(0x3d, 0xdb, 0xd9)),
+                    (Mode::Saves, Color::Rgb(0xff, 0x7e, 0xb6)),
+                    (Mode::Export, Color::Rgb(0x82, 0xcf, 0xff)),
+                    (Mode::Help, Color::Rgb(0xa5, 0x6e, 0xff)),
+                ]),
+            },
+            BuiltinTheme::Light => Theme {
+                background: Color::Rgb(0xff, 0xff, 0xff),
+                popup: Color::Rgb(0xf4, 0xf4, 0xf4),
+                status_line: Color::Rgb(0xe0, 0xe0, 0xe0),
+                selection: Color::Rgb(0xc6, 0xc6, 0xc6),
+                marked: Color::Rgb(0xe8, 0xda, 0xff),
+                text: Color::Rgb(0x16, 0x16, 0x16),
+                muted: Color::Rgb(0x6f, 0x6f, 0x6f),
+                notification: Color::Rgb(0x16, 0x16, 0x16),
+                modes: HashMap::from([
+                    (Mode::Normal, Color::Rgb(0x0f, 0x62, 0xfe)),
+                    (Mode::Visual, Color::Rgb(0x69, 0x29, 0xc4)),
+                    (Mode::CreateTag, Color::Rgb(0x19, 0x80, 0x38)),
+                    (Mode::ShowTags, Color::Rgb(0xba, 0x4e, 0x00)),
+                    (Mode::EditTag, Color::Rgb(0x19, 0x80, 0x38)),
+                    (Mode::Insert, Color::Rgb(0x19, 0x80, 0x38)),
+                    (Mode::RemoveTag, Color::Rgb(0xa2, 0x19, 0x1f)),
+                    (Mode::ShowCycles, Color::Rgb(0xda, 0x1e, 0x28)),
+                    (Mode::Issues, Color::Rgb(0x8e, 0x6a, 0x00)),
+                    (Mode::ScanErrors, Color::Rgb(0xd0, 0x26, 0x70)),
+                    (Mode::Search, Color::Rgb(0x00, 0x7d, 0x79)),
+                    (Mode::Filter, Color::Rgb(0x00, 0x72, 0xc3)),
+                    (Mode::Profiles, Color::Rgb(0x0e, 0x60, 0x27)),
+                    (Mode::NameProfile, Color::Rgb(0x0e, 0x60, 0x27)),
+                    (Mode::Diff, Color::Rgb(0x00, 0x5d, 0x5d)),
+                    (Mode::Saves, Color::Rgb(0x9f, 0x18, 0x53)),
+                    (Mode::Export, Color::Rgb(0x00, 0x2d, 0x9c)),
+                    (Mode::Help, Color::Rgb(0x8a, 0x3f, 0xfc)),
+                ]),
+            },
+        }
+    }
+}
+
+/// A [`BuiltinTheme`] with some of its colors replaced.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub base: BuiltinTheme,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub popup: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_line: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marked: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub muted: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notification: Option<Color>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub modes: HashMap<Mode, Color>,
+}
+impl ThemeConfig {
+    pub fn theme(&self) -> Theme {
+        let mut res = self.base.theme();
+        let overrides = [
+            (&mut res.background, self.background),
+            (&mut res.popup, self.popup),
+            (&mut res.status_line, self.status_line),
+            (&mut res.selection, self.selection),
+            (&mut res.marked, self.marked),
+            (&mut res.text, self.text),
+            (&mut res.muted, self.muted),
+            (&mut res.notification, self.notification),
+        ];
+        for (color, custom) in overrides {
+            if let Some(custom) = custom {
+                *color = custom;
+            }
+        }
+        res.modes.extend(&self.modes);
+        res
+    }
+}
@@ -4,4 +4,3 @@ pub use status_line::StatusLine;
 pub mod form;
 
 pub mod popup_manager;
-pub use popup_manager::PopupManager;
@@ -48,6 +48,7 @@ pub struct FormWidget<T> {
 #[derive(Default)]
 pub struct FormState<T: FormSpec> {
     pub background_color: Color,
+    pub text_color: Color,
     selected_color: Color,
     curr_buff: Input,
     index: usize,
@@ -88,7 +89,7 @@ impl<T: FormSpec> StatefulWidget for &FormWidget<T> {
             let foreground_color = if selected {
                 state.spec.selected_color()
             } else {
-                Color::default()
+                state.text_color
             };
             let input = InputBox {
                 title: prompt,
@@ -143,10 +144,21 @@ impl<T: FormSpec> FormState<T> {
         res
     }
 }
+impl<T: FormSpec> FormState<T> {
+    /// Starts over from the first field with the values of `spec`.
+    pub fn fill(&mut self, spec: T) {
+        self.spec = spec;
+        self.index = 0;
+        self.curr_buff = Input::default().with_value(self.spec.read_key(0).unwrap_or_default());
+        self.selected_color = self.spec.selected_color();
+        self.cursor_pos = Default::default();
+    }
+}
 impl<T: FormSpec + Default> FormState<T> {
     pub fn reset(&mut self) {
         let FormState {
             background_color: _,
+            text_color: _,
             selected_color,
             curr_buff,
             index,
@@ -268,6 +280,15 @@ impl FormSpec for TagForm {
         self.color
     }
 }
+impl From<&Tag> for TagForm {
+    fn from(tag: &Tag) -> Self {
+        Self {
+            name: tag.name.clone(),
+            score: Some(tag.score),
+            color: tag.color,
+        }
+    }
+}
 impl Default for TagForm {
     fn default() -> Self {
         let (r, g, b) = random_color();
@@ -278,3 +299,43 @@ impl Default for TagForm {
         }
     }
 }
+
+/// Name of a [`crate::mods::Profile`].
+#[derive(Debug, Default)]
+pub struct ProfileForm {
+    name: String,
+    color: Color,
+}
+impl FormSpec for ProfileForm {
+    const PROMPTS: &'static [&'static str] = &["Profile name"];
+    type Output = String;
+
+    fn try_into_key(&mut self, key: usize, content: &str) -> FormPoll<Self::Output> {
+        match key {
+            0 if !content.trim().is_empty() => {
+                self.name = content.trim().to_owned();
+                FormPoll::Done(self.name.clone())
+            }
+            _ => FormPoll::Invalid,
+        }
+    }
+
+    fn read_key(&self, key: usize) -> Option<String> {
+        match key {
+            0 if !self.name.is_empty() => Some(self.name.clone()),
+            _ => None,
+        }
+    }
+
+    fn selected_color(&self) -> Color {
+        self.color
+    }
+}
+impl ProfileForm {
+    pub fn new(name: &str, color: Color) -> Self {
+        Self {
+            name: name.to_owned(),
+            color,
+        }
+    }
+}
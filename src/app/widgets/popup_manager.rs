@@ -1,11 +1,6 @@
-use std::collections::HashMap;
-
-use ratatui::{
-    Frame,
-    layout::{Constraint, Flex, Layout, Rect},
-    style::Color,
-};
+use ratatui::style::Color;
 
+#[allow(dead_code)]
 pub struct PopupManager {
     background_color: Color,
 }
@@ -2,12 +2,16 @@ use ratatui::{
     Frame,
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style, Stylize as _},
+    style::{Style, Stylize as _},
     text::{Line, Span},
     widgets::{StatefulWidget, Widget},
 };
 
-use crate::app::Mode;
+use crate::app::{
+    Mode,
+    keymap::{Action, Keymap},
+    theme::Theme,
+};
 
 #[derive(Debug, Default)]
 pub struct StatusLine {
@@ -28,49 +32,168 @@ impl StatusLine {
 pub struct StatusLineWidget;
 #[derive(Debug, Default)]
 pub struct StatusLineState {
-    pub background_color: Color,
+    pub theme: Theme,
+    /// Edits that can be undone and redone, shown next to the hint.
+    pub history: (usize, usize),
+    /// Query the table is filtered with, shown next to the mode.
+    pub filter: String,
+    /// Profile the load order is saved to, shown next to the mode.
+    pub profile: Option<String>,
 
     left: Line<'static>,
     right: Line<'static>,
+    /// Shown instead of `right` until [`Self::dismiss`].
+    notification: Option<Line<'static>>,
 }
 impl StatusLineState {
     pub fn change_mode(&mut self, mode: Mode) {
         let text = mode.str_repr();
-        let mode_color = mode.color_repr();
+        let mode_color = self.theme.mode(mode);
 
-        self.left = Line::from(vec![
+        let mut spans = vec![
             Span::styled(
                 text,
                 Style::default()
                     .bg(mode_color)
-                    .fg(self.background_color)
+                    .fg(self.theme.status_line)
                     .bold(),
             ),
             Span::styled(
                 "",
-                Style::default().fg(mode_color).bg(self.background_color),
+                Style::default().fg(mode_color).bg(self.theme.status_line),
             ),
-        ])
-        .bg(self.background_color);
+        ];
+        if let Some(profile) = &self.profile {
+            spans.push(Span::styled(
+                format!(" {profile}"),
+                Style::default().fg(self.theme.mode(Mode::Profiles)),
+            ));
+        }
+        if !self.filter.is_empty() {
+            spans.push(Span::styled(
+                format!(" filter: {}", self.filter),
+                Style::default().fg(self.theme.mode(Mode::Filter)),
+            ));
+        }
+        self.left = Line::from(spans).bg(self.theme.status_line);
     }
-    pub fn change_hint(&mut self, mode: Mode, movement_delta: &str) {
+    pub fn change_hint(&mut self, mode: Mode, movement_delta: &str, keymap: &Keymap) {
         let text = if !movement_delta.is_empty() {
             movement_delta.to_string()
         } else {
-            match mode {
-                Mode::Normal => "Press ? for help, 'q' to quit",
-                Mode::CreateTag => "Creating new tag, ESC to go back.",
-                Mode::ShowTags => "Listing created tags, 'e' to edit, 'q' or ESC to go back",
-                Mode::Insert => "Inserting tag into selected mod, ESC to go back",
-            }
-            .to_string()
+            hint(mode, keymap)
         };
-        let mode_color = mode.color_repr();
+        let mode_color = self.theme.mode(mode);
 
-        self.right = Line::from(Span::styled(text, Style::default().fg(mode_color).bold()))
+        let mut spans = vec![];
+        if self.history != (0, 0) {
+            spans.push(Span::styled(
+                format!("{} undo, {} redo  ", self.history.0, self.history.1),
+                Style::default().fg(self.theme.muted),
+            ));
+        }
+        spans.push(Span::styled(text, Style::default().fg(mode_color).bold()));
+        self.right = Line::from(spans)
+            .alignment(ratatui::layout::Alignment::Right)
+            .bg(self.theme.status_line);
+    }
+    /// Shows `text` as the content of an input field.
+    pub fn prompt(&mut self, text: &str) {
+        self.right = Line::from(Span::styled(
+            text.to_owned(),
+            Style::default().fg(self.theme.text),
+        ))
+        .alignment(ratatui::layout::Alignment::Right)
+        .bg(self.theme.status_line);
+    }
+    /// Shows `text` instead of the hint until [`Self::dismiss`], changing
+    /// the mode or hint meanwhile doesn't hide it.
+    pub fn notify(&mut self, text: impl Into<String>) {
+        self.notification = Some(
+            Line::from(Span::styled(
+                text.into(),
+                Style::default().fg(self.theme.notification).bold(),
+            ))
             .alignment(ratatui::layout::Alignment::Right)
-            .bg(self.background_color);
+            .bg(self.theme.status_line),
+        );
     }
+    pub fn dismiss(&mut self) {
+        self.notification = None;
+    }
+}
+
+/// What `mode` is about followed by the keys of its main actions, actions
+/// without a key are left out.
+fn hint(mode: Mode, keymap: &Keymap) -> String {
+    use Action::*;
+    let back = (Back, "to go back");
+    let (about, actions): (&str, &[(Action, &str)]) = match mode {
+        Mode::Normal => ("", &[(Help, "for help"), (Quit, "to quit")]),
+        Mode::CreateTag => ("Creating new tag", &[back]),
+        Mode::ShowTags => (
+            "Listing created tags",
+            &[(EditTag, "to edit"), (DeleteTag, "to delete"), back],
+        ),
+        Mode::EditTag => ("Editing tag", &[back]),
+        Mode::Visual => (
+            "Selecting mods",
+            &[
+                (TagMod, "to tag them"),
+                (ToggleActive, "to toggle them"),
+                back,
+            ],
+        ),
+        Mode::Insert => (
+            "",
+            &[
+                (InsertTag, "to tag the selected mods"),
+                (RemoveTag, "to untag them"),
+                back,
+            ],
+        ),
+        Mode::RemoveTag => ("", &[(RemoveTag, "to remove the highlighted tag"), back]),
+        Mode::ShowCycles => ("Listing load order cycles", &[back]),
+        Mode::Issues => ("", &[(JumpToIssue, "to jump to the offending mod"), back]),
+        Mode::ScanErrors => ("Listing mods that couldn't be loaded", &[back]),
+        Mode::Search => ("Searching by name, packageId or author", &[]),
+        Mode::Profiles => (
+            "",
+            &[
+                (SwitchProfile, "to switch"),
+                (NewProfile, "new"),
+                (DuplicateProfile, "duplicate"),
+                (RenameProfile, "rename"),
+                (DiffProfile, "compare"),
+                back,
+            ],
+        ),
+        Mode::NameProfile => ("Naming profile", &[back]),
+        Mode::Diff => ("Comparing load orders", &[back]),
+        Mode::Saves => (
+            "",
+            &[(ImportSave, "to import the save's mods as a profile"), back],
+        ),
+        Mode::Export => ("", &[(Export, "to export in the highlighted format"), back]),
+        Mode::Filter => (
+            "tag:, author:, name:, id:, source:, is:active, and, or, not",
+            &[],
+        ),
+        Mode::Help => ("Listing key bindings", &[back]),
+    };
+    let keys = actions.iter().filter_map(|&(action, what)| {
+        let key = keymap.first_key(mode, action)?;
+        Some(if key.starts_with('<') {
+            format!("{key} {what}")
+        } else {
+            format!("'{key}' {what}")
+        })
+    });
+    std::iter::once(about.to_owned())
+        .filter(|about| !about.is_empty())
+        .chain(keys)
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl StatefulWidget for &StatusLineWidget {
@@ -83,6 +206,41 @@ impl StatefulWidget for &StatusLineWidget {
             .areas(area);
 
         (&state.left).render(left, buf);
-        (&state.right).render(right, buf);
+        state
+            .notification
+            .as_ref()
+            .unwrap_or(&state.right)
+            .render(right, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn hints_follow_the_keymap() {
+        let keymap = Keymap::new(&HashMap::new()).ok().unwrap();
+        assert_eq!(hint(Mode::Normal, &keymap), "'?' for help, 'q' to quit");
+
+        let overrides = HashMap::from([(
+            Mode::ShowTags,
+            HashMap::from([
+                ("dd".to_owned(), "Unbind".to_owned()),
+                ("E".to_owned(), "EditTag".to_owned()),
+                ("e".to_owned(), "Unbind".to_owned()),
+            ]),
+        )]);
+        let keymap = Keymap::new(&overrides).ok().unwrap();
+        assert_eq!(
+            hint(Mode::ShowTags, &keymap),
+            "Listing created tags, 'E' to edit, 'q' to go back"
+        );
+        assert_eq!(
+            hint(Mode::CreateTag, &keymap),
+            "Creating new tag, <Esc> to go back"
+        );
     }
 }
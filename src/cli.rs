@@ -0,0 +1,160 @@
+//! Commands that run without the TUI
+//!
+//! `r2m2 diff <from> [to]` prints the [`LoadOrderDiff`] of two load orders,
+//! `to` defaults to the game's `ModsConfig.xml`. Each side is `game`, a path
+//! to a `ModsConfig.xml` or RimSort list, a RimPy list, a `.rws` save or a
+//! list of packageIds, or a profile name.
+//!
+//! `r2m2 save <path>` lists the mods a save was played with and which of them
+//! aren't installed.
+//!
+//! `r2m2 export <format> [path]` writes every known mod in one of the
+//! [`Format`]s, to stdout without a path. With a [`ListFormat`] it writes the
+//! load order of `from` instead, `game` by default:
+//! `r2m2 export <rimsort|rimpy> [path] [from]`.
+//!
+//! `r2m2 import <path> <profile>` saves a RimSort or RimPy list as a profile.
+use std::path::Path;
+
+use color_eyre::{Result, eyre::eyre};
+
+use crate::{
+    app::Persistent,
+    config::Config,
+    mods::{
+        Profile,
+        diff::LoadOrderDiff,
+        export::{self, Format},
+        game::{ModsConfigData, SaveMeta},
+        mod_list::{self, ListFormat},
+        scan,
+    },
+};
+
+pub const USAGE: &str = "Usage: r2m2 [diff <from> [to] | save <path> \
+    | export <md|csv|json|html> [path] | export <rimsort|rimpy> [path] [from] \
+    | import <path> <profile>]";
+
+/// Runs the command in `args`, the program name excluded.
+pub fn run(args: &[String], mut persistent: Persistent, config: &Config) -> Result<()> {
+    match args {
+        [cmd, from, rest @ ..] if cmd == "diff" && rest.len() <= 1 => {
+            let to = rest.first().map_or("game", String::as_str);
+            let diff = LoadOrderDiff::new(
+                &load_order(from, &persistent, config)?,
+                &load_order(to, &persistent, config)?,
+            );
+            print!("{diff}");
+            Ok(())
+        }
+        [cmd, path] if cmd == "save" => {
+            let meta = read_save(Path::new(path))?;
+            let installed = scan::scan_sources(&config.sources).mods;
+            let missing = meta.missing(&installed);
+            println!("RimWorld {}, {} mods", meta.game_version, meta.mods.len());
+            for saved in &meta.mods {
+                let note = match &saved.steam_id {
+                    _ if !missing.contains(&saved) => String::new(),
+                    Some(steam_id) => format!(" (not installed, workshop {steam_id})"),
+                    None => " (not installed)".to_owned(),
+                };
+                let marker = if note.is_empty() { ' ' } else { '!' };
+                println!("{marker} {}  {}{note}", saved.package_id, saved.name);
+            }
+            println!(
+                "{} of {} mods aren't installed",
+                missing.len(),
+                meta.mods.len()
+            );
+            Ok(())
+        }
+        [cmd, format, rest @ ..] if cmd == "export" && rest.len() <= 2 => {
+            rescan(&mut persistent, config);
+            let text = match format.parse::<ListFormat>() {
+                Ok(format) => {
+                    let from = rest.get(1).map_or("game", String::as_str);
+                    let active_mods = load_order(from, &persistent, config)?;
+                    let mods_config = match std::fs::read_to_string(&config.mods_config) {
+                        Ok(xml) => ModsConfigData::from_xml(&xml)?,
+                        Err(_) => ModsConfigData::default(),
+                    };
+                    mod_list::write(format, &active_mods, &persistent.mods, &mods_config)?
+                }
+                Err(_) if rest.len() == 2 => return Err(eyre!(USAGE)),
+                Err(_) => {
+                    let format: Format = format.parse().map_err(|e: String| eyre!(e))?;
+                    export::export(format, persistent.mods.iter(), &persistent.tags)
+                }
+            };
+            match rest.first() {
+                Some(path) => std::fs::write(path, text)?,
+                None => print!("{text}"),
+            }
+            Ok(())
+        }
+        [cmd, path, name] if cmd == "import" => {
+            rescan(&mut persistent, config);
+            let list = mod_list::read(&std::fs::read_to_string(path)?, &persistent.mods)?;
+            for id in &list.unknown {
+                println!("! {id} (not installed)");
+            }
+            println!(
+                "{} mods saved to profile {name}, {} aren't installed",
+                list.active_mods.len(),
+                list.unknown.len()
+            );
+            persistent.profiles.upsert(Profile {
+                name: name.clone(),
+                active_mods: list.active_mods,
+            });
+            std::fs::write(crate::MOD_INFO, persistent.to_ron()?)?;
+            Ok(())
+        }
+        _ => Err(eyre!(USAGE)),
+    }
+}
+
+/// Active packageIds of `source`.
+///
+/// Paths win over profiles of the same name. `.xml` files are read as
+/// [`ListFormat`]s, files other than `.xml` and `.rws` are read
+/// as one packageId per line, blank lines and lines starting with `#` are
+/// skipped.
+pub fn load_order(source: &str, persistent: &Persistent, config: &Config) -> Result<Vec<String>> {
+    let path = match source {
+        "game" => config.mods_config.as_path(),
+        _ => Path::new(source),
+    };
+    if path.is_file() {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        if extension.is_some_and(|ext| ext.eq_ignore_ascii_case("rws")) {
+            return Ok(read_save(path)?.active_mods());
+        }
+        let text = std::fs::read_to_string(path)?;
+        if extension.is_some_and(|ext| ext.eq_ignore_ascii_case("xml")) {
+            return Ok(mod_list::read(&text, &persistent.mods)?.active_mods);
+        }
+        return Ok(text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_owned)
+            .collect());
+    }
+    persistent
+        .profiles
+        .get_by_name(source)
+        .map(|profile| profile.active_mods.clone())
+        .ok_or_else(|| eyre!("No profile or file called {source}"))
+}
+
+fn read_save(path: &Path) -> Result<SaveMeta> {
+    SaveMeta::open(path)?.ok_or_else(|| eyre!("{} has no mod list", path.display()))
+}
+
+/// Refreshes which mods are installed, as the TUI does on startup.
+fn rescan(persistent: &mut Persistent, config: &Config) {
+    persistent
+        .mods
+        .merge_scanned(scan::scan_sources(&config.sources).mods);
+}
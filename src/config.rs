@@ -0,0 +1,68 @@
+//! User configuration, read from `./config.ron`
+//!
+//! Every field is optional, missing ones fall back to a Steam install on Linux.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::{Mode, theme::ThemeConfig},
+    mods::scan::{ModSource, Source},
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Scanned in order, a packageId found twice keeps the first one.
+    pub sources: Vec<ModSource>,
+    /// Path to the game's `ModsConfig.xml`.
+    pub mods_config: PathBuf,
+    /// Folder of the `.rws` saves listed by [`Mode::Saves`].
+    pub saves: PathBuf,
+    /// Key sequences bound to action names per mode, on top of
+    /// [`crate::app::keymap::defaults`], e.g. `{Normal: {"<C-d>": "MoveBottom"}}`.
+    pub keymap: HashMap<Mode, HashMap<String, String>>,
+    pub theme: ThemeConfig,
+}
+impl Default for Config {
+    fn default() -> Self {
+        let home: PathBuf = std::env::var("HOME").unwrap_or_default().into();
+        let steam = home.join(".local/share/Steam/steamapps");
+        let game = steam.join("common/RimWorld");
+        let user_data = home.join(".config/unity3d/Ludeon Studios/RimWorld by Ludeon Studios");
+        Self {
+            sources: vec![
+                ModSource {
+                    source: Source::Game,
+                    path: game.join("Data"),
+                },
+                ModSource {
+                    source: Source::Local,
+                    path: game.join("Mods"),
+                },
+                ModSource {
+                    source: Source::Workshop,
+                    path: steam.join("workshop/content/294100"),
+                },
+            ],
+            mods_config: user_data.join("Config/ModsConfig.xml"),
+            saves: user_data.join("Saves"),
+            keymap: HashMap::new(),
+            theme: ThemeConfig::default(),
+        }
+    }
+}
+impl Config {
+    /// Reads the config at `path`, the default one is used if there is none.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(buf) => Ok(ron::de::from_str(&buf)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -1,27 +1,33 @@
 pub mod app;
+pub mod cli;
+pub mod config;
 pub mod mods;
 
 use std::{
     fs::File,
     io::{Read, Write},
+    path::Path,
     time::Duration,
 };
 
-use app::Model;
+use app::{Model, keymap::Keymap};
+use config::Config;
 use crossterm::event;
-use mods::{app_mod::Mod, game::ModMetaData};
-use ron::ser::PrettyConfig;
+use mods::game::ModsConfigData;
 
-use color_eyre::Result;
-use quick_xml::de::from_str;
+use color_eyre::{Result, eyre::eyre};
 use ratatui::{Terminal, prelude::CrosstermBackend};
+use tracing::{error, warn};
 use tracing_error::ErrorLayer;
 use tracing_subscriber::{layer::SubscriberExt as _, util::SubscriberInitExt};
 
+/// Tags, profiles and every mod seen, see [`app::Persistent`].
+pub const MOD_INFO: &str = "./mod_info.ron";
+
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     mut model: Model,
-) -> Result<app::Persistent> {
+) -> Result<(app::Persistent, ModsConfigData)> {
     while !model.should_close() {
         terminal.draw(|f| model.view(f))?;
 
@@ -29,6 +35,9 @@ fn run_app(
         if event::poll(Duration::from_millis(200))? {
             let ev = event::read()?;
             msg = app::try_message(&model, ev);
+            if msg.is_some() {
+                model.dismiss_notification();
+            }
         }
 
         while msg.is_some() {
@@ -37,23 +46,38 @@ fn run_app(
     }
     Ok(model.result())
 }
-fn read_dir() -> Result<app::Persistent> {
-    let path = std::env::args().nth(1).unwrap();
-    let mut mods = vec![];
-    for path in std::fs::read_dir(path)? {
-        let mut path = path?.path();
-        if !path.is_dir() {
-            continue;
+/// Reads [`MOD_INFO`], starting over if it's missing or unreadable.
+///
+/// An unreadable file is copied next to it before it gets overwritten on
+/// exit, the error is returned to be shown to the user.
+fn read_mod_info() -> Result<(app::Persistent, Option<String>)> {
+    let mut buf = String::new();
+    match File::open(MOD_INFO) {
+        Ok(mut f) => f.read_to_string(&mut buf)?,
+        Err(_) => return Ok((Default::default(), None)),
+    };
+    match app::Persistent::from_ron(&buf) {
+        Ok(persistent) => Ok((persistent, None)),
+        Err(e) => {
+            error!("Couldn't read {MOD_INFO}: {e}");
+            let backup = format!("{MOD_INFO}.bak");
+            std::fs::copy(MOD_INFO, &backup)?;
+            let text =
+                format!("Couldn't read {MOD_INFO} ({e}), starting over, it was copied to {backup}");
+            Ok((Default::default(), Some(text)))
+        }
+    }
+}
+/// Returns `None` when there is no `ModsConfig.xml` to manage, in which case
+/// nothing will be written back on exit.
+fn read_mods_config(path: &Path) -> Result<Option<ModsConfigData>> {
+    match std::fs::read_to_string(path) {
+        Ok(xml) => Ok(Some(ModsConfigData::from_xml(&xml)?)),
+        Err(e) => {
+            warn!("Couldn't read {}: {e}", path.display());
+            Ok(None)
         }
-        path.push("About/About.xml");
-        let xml = std::fs::read_to_string(path).unwrap();
-        let metadata: ModMetaData = from_str(&xml)?;
-        mods.push(Mod::new(metadata));
     }
-    Ok(app::Persistent {
-        mods: mods.into(),
-        tags: Default::default(),
-    })
 }
 fn main() -> Result<()> {
     let log_file = std::fs::File::create("./log")?;
@@ -69,26 +93,43 @@ fn main() -> Result<()> {
         .init();
 
     color_eyre::install()?;
-    let persistent: app::Persistent = match File::open("./mod_info.ron") {
-        Ok(mut f) => {
-            let mut buf = String::new();
-            f.read_to_string(&mut buf)?;
-            match ron::de::from_str(&buf) {
-                Ok(v) => v,
-                Err(_) => read_dir()?,
-            }
+    let (persistent, mod_info_error) = read_mod_info()?;
+    let config = Config::load(Path::new("./config.ron"))?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Some(e) = &mod_info_error {
+            eprintln!("{e}");
         }
-        Err(_) => read_dir()?,
-    };
+        return cli::run(&args, persistent, &config);
+    }
+    let mods_config = read_mods_config(&config.mods_config)?;
+    let keymap = Keymap::new(&config.keymap).map_err(|errors| {
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        eyre!("Invalid key bindings:\n{}", errors.join("\n"))
+    })?;
     let mut terminal = ratatui::init();
-    let model = Model::new(persistent);
-    let res = run_app(&mut terminal, model)?;
+    let mut model = Model::new(
+        persistent,
+        mods_config.clone(),
+        config.sources,
+        config.saves,
+        keymap,
+        config.theme.theme(),
+    );
+    model.rescan();
+    if let Some(e) = mod_info_error {
+        model.notify(e);
+    }
+    let res = run_app(&mut terminal, model);
     ratatui::restore();
-    let mut buff = String::new();
-    ron::ser::to_writer_pretty(&mut buff, &res, PrettyConfig::default())?;
+    let (persistent, new_mods_config) = res?;
     {
-        let mut file = File::create("./mod_info.ron")?;
-        file.write_all(buff.as_bytes())?;
+        let mut file = File::create(MOD_INFO)?;
+        file.write_all(persistent.to_ron()?.as_bytes())?;
+    }
+    if mods_config.is_some() {
+        let mut file = File::create(&config.mods_config)?;
+        file.write_all(new_mods_config.to_xml()?.as_bytes())?;
     }
     Ok(())
 }
@@ -4,11 +4,26 @@
 //! [`OrderedItems<T>`] is a container of <T> that is internally ordered
 //! [`TagSpans`] may be used to get a display representation to
 //! a collection of [`Tag`]. It blends background_color with each [`Tag`]'s color.
+//! [`sorter`] computes the load order of the active mods.
+//! [`validation`] reports missing dependencies and incompatibilities.
+//! [`scan`] reads the installed mods from disk.
+//! [`Profile`] is a named load order.
+//! [`diff`] compares two load orders.
+//! [`export`] writes mod lists to share.
+//! [`mod_list`] reads and writes the load orders of other mod managers.
 pub mod app_mod;
+pub mod diff;
+pub mod export;
 pub mod game;
+pub mod mod_list;
+pub mod profile;
+pub mod scan;
+pub mod sorter;
 pub mod tag;
+pub mod validation;
 
 pub use app_mod::Mod;
+pub use profile::Profile;
 pub use tag::Tag;
 
 use std::cmp::Ordering;
@@ -67,6 +82,13 @@ mod private {
                 .unwrap_or_else(|i| i);
             self.data.insert(idx, other);
         }
+        pub fn remove(&mut self, name: &str) -> Option<T> {
+            let idx = self
+                .data
+                .iter()
+                .position(|item| item.identifier() == name)?;
+            Some(self.data.remove(idx))
+        }
         pub const fn len(&self) -> usize {
             self.data.len()
         }
@@ -107,3 +129,40 @@ mod private {
         }
     }
 }
+
+/// Fixtures shared by the tests of the submodules.
+#[cfg(test)]
+pub(crate) mod test_util {
+    use std::path::PathBuf;
+
+    use super::{Mod, game::ModsConfigData, scan::Source};
+
+    /// A local mod whose About.xml holds `extra` besides its name and
+    /// packageId, `name` is written as is and may need escaping.
+    pub fn game_mod(name: &str, package_id: &str, extra: &str) -> Mod {
+        installed_mod(name, package_id, extra, Source::Local, "")
+    }
+    /// [`game_mod`] found in `path` of `source`.
+    pub fn installed_mod(
+        name: &str,
+        package_id: &str,
+        extra: &str,
+        source: Source,
+        path: &str,
+    ) -> Mod {
+        let xml = format!(
+            "<ModMetaData><name>{name}</name><packageId>{package_id}</packageId>{extra}</ModMetaData>"
+        );
+        Mod::new(
+            quick_xml::de::from_str(&xml).unwrap(),
+            source,
+            PathBuf::from(path),
+        )
+    }
+    pub fn config(active: &[&str]) -> ModsConfigData {
+        ModsConfigData {
+            active_mods: active.iter().map(|id| id.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+}
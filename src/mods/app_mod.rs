@@ -1,9 +1,15 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::HashSet, fmt, path::PathBuf};
 
 use ratatui::{style::Color, text::Line};
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
-use super::{Item, OrderedItems, game::ModMetaData, tag::Tag};
+use super::{
+    Item, OrderedItems,
+    game::ModMetaData,
+    scan::Source,
+    tag::{SelectedTag, Tag, TagRef, TagSpans},
+};
 
 impl Item for Mod {
     fn identifier(&self) -> &str {
@@ -11,7 +17,17 @@ impl Item for Mod {
     }
 
     fn patch(&mut self, other: Self) {
-        *self = other;
+        let Mod {
+            metadata,
+            tags: _,
+            source,
+            path,
+            missing,
+        } = other;
+        self.metadata = metadata;
+        self.source = source;
+        self.path = path;
+        self.missing = missing;
     }
 
     fn vec_order(&self, other: &Self) -> Ordering {
@@ -34,24 +50,269 @@ impl Item for Mod {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Mod {
     pub metadata: ModMetaData,
-    tags: OrderedItems<Tag>,
+    /// Names of the tags in [`crate::app::Persistent::tags`].
+    tags: OrderedItems<TagRef>,
+    #[serde(default)]
+    pub source: Source,
+    /// Folder the mod was found in.
+    #[serde(default)]
+    pub path: PathBuf,
+    /// The mod's folder wasn't found on the last scan.
+    #[serde(default)]
+    pub missing: bool,
 }
 impl Mod {
-    pub fn new(metadata: ModMetaData) -> Self {
+    pub fn new(metadata: ModMetaData, source: Source, path: PathBuf) -> Self {
         Self {
             metadata,
-            tags: Default::default(),
+            tags: vec![].into(),
+            source,
+            path,
+            missing: false,
+        }
+    }
+
+    /// Builds a mod from one saved before tags were referenced by name.
+    pub(crate) fn from_legacy(
+        metadata: ModMetaData,
+        tags: &[Tag],
+        source: Source,
+        path: PathBuf,
+        missing: bool,
+    ) -> Self {
+        Self {
+            tags: tags.iter().map(TagRef::from).collect::<Vec<_>>().into(),
+            missing,
+            ..Self::new(metadata, source, path)
         }
     }
 
-    pub fn tags_styled_line(&self, bg_color: Color, is_selected: bool) -> Line {
-        self.tags.styled_line(bg_color, is_selected)
+    pub fn tags(&self) -> &OrderedItems<TagRef> {
+        &self.tags
+    }
+    pub fn has_tag(&self, name: &str) -> bool {
+        self.tags.get_by_name(name).is_some()
+    }
+    /// Workshop item the mod was downloaded from, its folder is named after it.
+    pub fn workshop_id(&self) -> Option<&str> {
+        match self.source {
+            Source::Workshop => self.path.file_name()?.to_str(),
+            _ => None,
+        }
+    }
+    pub fn workshop_url(&self) -> Option<String> {
+        self.workshop_id()
+            .map(|id| format!("https://steamcommunity.com/sharedfiles/filedetails/?id={id}"))
+    }
+    pub fn tags_styled_line(
+        &self,
+        registry: &OrderedItems<Tag>,
+        bg_color: Color,
+        muted_color: Color,
+        is_selected: bool,
+    ) -> Line<'static> {
+        let selected = if is_selected {
+            SelectedTag::All
+        } else {
+            SelectedTag::None
+        };
+        Line::from(
+            TagSpans::new(
+                selected,
+                bg_color,
+                muted_color,
+                registry.resolve(&self.tags),
+            )
+            .collect::<Vec<_>>(),
+        )
+    }
+    /// Tags with only the one at `idx` highlighted.
+    pub fn tags_line_highlighting(
+        &self,
+        registry: &OrderedItems<Tag>,
+        bg_color: Color,
+        muted_color: Color,
+        idx: usize,
+    ) -> Line<'static> {
+        Line::from(
+            TagSpans::new(
+                SelectedTag::Index(idx),
+                bg_color,
+                muted_color,
+                registry.resolve(&self.tags),
+            )
+            .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// Changes made by [`OrderedItems<Mod>::merge_scanned`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub updated: usize,
+}
+impl fmt::Display for MergeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} removed, {} updated",
+            self.added, self.removed, self.updated
+        )
     }
 }
 
 impl OrderedItems<Mod> {
-    pub fn upsert_tag_to(&mut self, mod_idx: usize, tag: Tag) {
-        self.get_mut(mod_idx).unwrap().tags.upsert(tag);
+    /// Merges the mods found on disk, known mods keep their tags and the
+    /// ones that weren't found are marked as missing.
+    ///
+    /// Mods that show up again count as added.
+    pub fn merge_scanned(&mut self, scanned: Vec<Mod>) -> MergeSummary {
+        let mut summary = MergeSummary::default();
+        let found: HashSet<String> = scanned.iter().map(|m| m.identifier().to_owned()).collect();
+        for game_mod in scanned {
+            match self.get_by_name(game_mod.identifier()) {
+                None => summary.added += 1,
+                Some(existing) if existing.missing => summary.added += 1,
+                Some(existing)
+                    if existing.metadata != game_mod.metadata
+                        || existing.source != game_mod.source
+                        || existing.path != game_mod.path =>
+                {
+                    summary.updated += 1
+                }
+                Some(_) => {}
+            }
+            self.upsert(game_mod);
+        }
+        for idx in 0..self.len() {
+            let game_mod = self.get_mut(idx).unwrap();
+            if !game_mod.missing && !found.contains(game_mod.identifier()) {
+                game_mod.missing = true;
+                summary.removed += 1;
+            }
+        }
+        self.sort();
+        summary
+    }
+
+    /// Points every reference to the tag called `name` to `tag`, removing
+    /// them if `None`.
+    pub fn replace_tag(&mut self, name: &str, tag: Option<&Tag>) {
+        for idx in 0..self.len() {
+            let tags = &mut self.get_mut(idx).unwrap().tags;
+            if tags.remove(name).is_none() {
+                continue;
+            }
+            if let Some(tag) = tag {
+                // a rename may collide with a tag the mod already has
+                tags.remove(&tag.name);
+                tags.upsert(tag.into());
+            }
+        }
         self.sort();
     }
+    /// Refreshes the tags' scores from `registry`, references to tags it
+    /// doesn't have are dropped.
+    pub fn sync_tags(&mut self, registry: &OrderedItems<Tag>) {
+        for idx in 0..self.len() {
+            let game_mod = self.get_mut(idx).unwrap();
+            let refs: Vec<TagRef> = registry
+                .resolve(&game_mod.tags)
+                .into_iter()
+                .map(TagRef::from)
+                .collect();
+            if refs.len() != game_mod.tags.len() {
+                warn!("Dropping unknown tags of {}", game_mod.metadata.package_id);
+            }
+            game_mod.tags = refs.into();
+        }
+        self.sort();
+    }
+    /// Replaces the tags of `package_id`, call [`Self::sync_tags`] afterwards.
+    pub fn set_tags(&mut self, package_id: &str, names: &[String]) {
+        let Some(idx) = self.iter().position(|m| m.identifier() == package_id) else {
+            return;
+        };
+        let names: Vec<TagRef> = names.iter().cloned().map(TagRef::from).collect();
+        self.get_mut(idx).unwrap().tags = names.into();
+    }
+    pub fn upsert_tag_to(&mut self, package_ids: &HashSet<String>, tag: &Tag) {
+        for idx in 0..self.len() {
+            let game_mod = self.get_mut(idx).unwrap();
+            if package_ids.contains(game_mod.identifier()) {
+                game_mod.tags.upsert(tag.into());
+            }
+        }
+        self.sort();
+    }
+    /// Returns how many of the mods had the tag.
+    pub fn remove_tag_from(&mut self, package_ids: &HashSet<String>, name: &str) -> usize {
+        let mut res = 0;
+        for idx in 0..self.len() {
+            let game_mod = self.get_mut(idx).unwrap();
+            if package_ids.contains(game_mod.identifier()) && game_mod.tags.remove(name).is_some() {
+                res += 1;
+            }
+        }
+        self.sort();
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::test_util::installed_mod;
+
+    fn game_mod(package_id: &str, path: &str) -> Mod {
+        installed_mod(package_id, package_id, "", Source::Local, path)
+    }
+    fn ids(package_ids: &[&str]) -> HashSet<String> {
+        package_ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn rescans_count_changes_and_keep_tags() {
+        let favorite = Tag {
+            name: "favorite".to_owned(),
+            score: 0,
+            color: Color::Red,
+        };
+        let mut gone = game_mod("c.c", "Mods/c");
+        gone.missing = true;
+        let mut mods: OrderedItems<Mod> = vec![
+            game_mod("a.a", "Mods/a"),
+            game_mod("b.b", "Mods/b"),
+            gone,
+            game_mod("e.e", "Mods/e"),
+        ]
+        .into();
+        mods.upsert_tag_to(&ids(&["a.a", "b.b"]), &favorite);
+
+        let scanned = || {
+            vec![
+                game_mod("a.a", "Mods/a"),
+                game_mod("b.b", "Mods/b moved"),
+                game_mod("c.c", "Mods/c"),
+                game_mod("d.d", "Mods/d"),
+            ]
+        };
+        let summary = mods.merge_scanned(scanned());
+        assert_eq!((summary.added, summary.removed, summary.updated), (2, 1, 1));
+        assert_eq!(summary.to_string(), "2 added, 1 removed, 1 updated");
+
+        assert_eq!(mods.len(), 5);
+        let get = |id| mods.get_by_name(id).unwrap();
+        assert!(get("a.a").has_tag("favorite"));
+        assert!(get("b.b").has_tag("favorite"));
+        assert_eq!(get("b.b").path, PathBuf::from("Mods/b moved"));
+        assert!(!get("c.c").missing);
+        assert!(get("e.e").missing);
+
+        let summary = mods.merge_scanned(scanned());
+        assert_eq!((summary.added, summary.removed, summary.updated), (0, 0, 0));
+        assert!(mods.get_by_name("a.a").unwrap().has_tag("favorite"));
+    }
 }
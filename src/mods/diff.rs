@@ -0,0 +1,235 @@
+//! Differences between two load orders
+//!
+//! [`LoadOrderDiff::new`] lines up two lists of packageIds. Mods only on the
+//! left were removed, mods only on the right were added and mods on both
+//! sides that don't keep their relative order were moved. PackageIds are
+//! compared case insensitively, like the game does.
+use std::{collections::HashSet, fmt};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    Kept,
+    Added,
+    Removed,
+    Moved,
+}
+impl Change {
+    /// Prefix of the plain text output.
+    pub fn marker(&self) -> char {
+        match self {
+            Change::Kept => ' ',
+            Change::Added => '+',
+            Change::Removed => '-',
+            Change::Moved => '~',
+        }
+    }
+}
+
+/// Both load orders, each packageId marked with how it changed.
+#[derive(Clone, Debug, Default)]
+pub struct LoadOrderDiff {
+    pub left: Vec<(String, Change)>,
+    pub right: Vec<(String, Change)>,
+}
+impl LoadOrderDiff {
+    pub fn new(left: &[String], right: &[String]) -> Self {
+        let lower = |ids: &[String]| -> Vec<String> {
+            ids.iter().map(|id| id.to_ascii_lowercase()).collect()
+        };
+        let (left_keys, right_keys) = (lower(left), lower(right));
+        let left_set: HashSet<&String> = left_keys.iter().collect();
+        let right_set: HashSet<&String> = right_keys.iter().collect();
+        let common = |keys: &[String], other: &HashSet<&String>| -> Vec<usize> {
+            (0..keys.len())
+                .filter(|&i| other.contains(&keys[i]))
+                .collect()
+        };
+        let left_common = common(&left_keys, &right_set);
+        let right_common = common(&right_keys, &left_set);
+
+        // Longest common subsequence of the shared mods, whatever isn't part
+        // of it changed places.
+        let (n, m) = (left_common.len(), right_common.len());
+        let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i][j] = if left_keys[left_common[i]] == right_keys[right_common[j]] {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+        let mut left_kept = HashSet::new();
+        let mut right_kept = HashSet::new();
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if left_keys[left_common[i]] == right_keys[right_common[j]] {
+                left_kept.insert(left_common[i]);
+                right_kept.insert(right_common[j]);
+                i += 1;
+                j += 1;
+            } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        let mark = |ids: &[String],
+                    keys: &[String],
+                    other: &HashSet<&String>,
+                    kept: &HashSet<usize>,
+                    missing: Change| {
+            ids.iter()
+                .zip(keys)
+                .enumerate()
+                .map(|(idx, (id, key))| {
+                    let change = if !other.contains(key) {
+                        missing
+                    } else if kept.contains(&idx) {
+                        Change::Kept
+                    } else {
+                        Change::Moved
+                    };
+                    (id.clone(), change)
+                })
+                .collect()
+        };
+        Self {
+            left: mark(left, &left_keys, &right_set, &left_kept, Change::Removed),
+            right: mark(right, &right_keys, &left_set, &right_kept, Change::Added),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.left
+            .iter()
+            .chain(&self.right)
+            .all(|(_, change)| *change == Change::Kept)
+    }
+    /// Number of added, removed and moved mods.
+    pub fn counts(&self) -> (usize, usize, usize) {
+        let count = |side: &[(String, Change)], wanted| {
+            side.iter().filter(|(_, change)| *change == wanted).count()
+        };
+        (
+            count(&self.right, Change::Added),
+            count(&self.left, Change::Removed),
+            count(&self.right, Change::Moved),
+        )
+    }
+}
+impl fmt::Display for LoadOrderDiff {
+    /// One line per changed mod, moved mods show their old and new position.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        for (id, change) in &self.left {
+            if *change == Change::Removed {
+                writeln!(f, "{} {id}", change.marker())?;
+            }
+        }
+        for (idx, (id, change)) in self.right.iter().enumerate() {
+            match change {
+                Change::Added => writeln!(f, "{} {id} at {}", change.marker(), idx + 1)?,
+                Change::Moved => {
+                    let from = self
+                        .left
+                        .iter()
+                        .position(|(other, _)| other.eq_ignore_ascii_case(id))
+                        .map_or(0, |i| i + 1);
+                    writeln!(f, "{} {id} {from} -> {}", change.marker(), idx + 1)?
+                }
+                _ => {}
+            }
+        }
+        let (added, removed, moved) = self.counts();
+        writeln!(f, "{added} added, {removed} removed, {moved} moved")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(left: &[&str], right: &[&str]) -> LoadOrderDiff {
+        let ids = |ids: &[&str]| -> Vec<String> { ids.iter().map(|id| id.to_string()).collect() };
+        LoadOrderDiff::new(&ids(left), &ids(right))
+    }
+    fn changes(side: &[(String, Change)]) -> Vec<(&str, Change)> {
+        side.iter().map(|(id, c)| (id.as_str(), *c)).collect()
+    }
+
+    #[test]
+    fn empty_lists_are_equal() {
+        let diff = diff(&[], &[]);
+        assert!(diff.is_empty());
+        assert_eq!(diff.counts(), (0, 0, 0));
+        assert_eq!(diff.to_string(), "No differences\n");
+    }
+
+    #[test]
+    fn ids_are_compared_case_insensitively() {
+        let diff = diff(&["Ludeon.RimWorld", "a"], &["ludeon.rimworld", "A"]);
+        assert!(diff.is_empty());
+        assert_eq!(changes(&diff.right)[0], ("ludeon.rimworld", Change::Kept));
+    }
+
+    #[test]
+    fn insertions_and_removals() {
+        let added = diff(&[], &["a", "b"]);
+        assert_eq!(
+            changes(&added.right),
+            [("a", Change::Added), ("b", Change::Added)]
+        );
+        assert_eq!(
+            added.to_string(),
+            "+ a at 1\n+ b at 2\n2 added, 0 removed, 0 moved\n"
+        );
+
+        let removed = diff(&["a", "b", "c"], &["b"]);
+        assert_eq!(
+            changes(&removed.left),
+            [
+                ("a", Change::Removed),
+                ("b", Change::Kept),
+                ("c", Change::Removed)
+            ]
+        );
+        assert_eq!(removed.counts(), (0, 2, 0));
+        assert_eq!(
+            removed.to_string(),
+            "- a\n- c\n0 added, 2 removed, 0 moved\n"
+        );
+    }
+
+    #[test]
+    fn only_mods_out_of_order_are_moved() {
+        let diff = diff(&["a", "b", "c", "d"], &["b", "c", "a", "d"]);
+        assert_eq!(
+            changes(&diff.right),
+            [
+                ("b", Change::Kept),
+                ("c", Change::Kept),
+                ("a", Change::Moved),
+                ("d", Change::Kept)
+            ]
+        );
+        assert_eq!(changes(&diff.left)[0], ("a", Change::Moved));
+        assert_eq!(
+            diff.to_string(),
+            "~ a 1 -> 3\n0 added, 0 removed, 1 moved\n"
+        );
+    }
+
+    #[test]
+    fn display_lists_removals_first() {
+        let diff = diff(&["a", "b", "c"], &["c", "a", "d"]);
+        assert_eq!(diff.counts(), (1, 1, 1));
+        assert_eq!(
+            diff.to_string(),
+            "- b\n~ a 1 -> 2\n+ d at 3\n1 added, 1 removed, 1 moved\n"
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//! Mod lists meant to be shared
+//!
+//! [`export`] writes the name, packageId, authors, tags, source and workshop
+//! link of each mod in one of the [`Format`]s. Tag colors are only kept by
+//! HTML and JSON.
+use std::{fmt::Write as _, str::FromStr};
+
+use ratatui::style::Color;
+use serde::Serialize;
+
+use super::{Mod, OrderedItems, Tag};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Markdown,
+    Csv,
+    Json,
+    Html,
+}
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Markdown, Format::Csv, Format::Json, Format::Html];
+    pub fn str_repr(&self) -> &'static str {
+        match self {
+            Format::Markdown => "Markdown",
+            Format::Csv => "CSV",
+            Format::Json => "JSON",
+            Format::Html => "HTML",
+        }
+    }
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Html => "html",
+        }
+    }
+}
+impl FromStr for Format {
+    type Err = String;
+
+    /// Either the name or the extension of a format, in any case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .into_iter()
+            .find(|f| s.eq_ignore_ascii_case(f.str_repr()) || s.eq_ignore_ascii_case(f.extension()))
+            .ok_or_else(|| format!("Unknown format {s}, expected md, csv, json or html"))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Row<'a> {
+    name: &'a str,
+    package_id: &'a str,
+    authors: Vec<&'a str>,
+    tags: Vec<RowTag<'a>>,
+    source: &'static str,
+    workshop_url: Option<String>,
+}
+#[derive(Serialize)]
+struct RowTag<'a> {
+    name: &'a str,
+    color: String,
+}
+impl<'a> Row<'a> {
+    fn new(game_mod: &'a Mod, registry: &'a OrderedItems<Tag>) -> Self {
+        Self {
+            name: &game_mod.metadata.name,
+            package_id: &game_mod.metadata.package_id,
+            authors: game_mod.metadata.author_names().collect(),
+            tags: registry
+                .resolve(game_mod.tags())
+                .into_iter()
+                .map(|tag| RowTag {
+                    name: &tag.name,
+                    color: css_color(tag.color),
+                })
+                .collect(),
+            source: game_mod.source.str_repr(),
+            workshop_url: game_mod.workshop_url(),
+        }
+    }
+    fn tag_names(&self) -> String {
+        let names: Vec<&str> = self.tags.iter().map(|t| t.name).collect();
+        names.join(", ")
+    }
+}
+
+/// `mods` in order, tags are looked up in `registry`.
+pub fn export<'a>(
+    format: Format,
+    mods: impl IntoIterator<Item = &'a Mod>,
+    registry: &'a OrderedItems<Tag>,
+) -> String {
+    let rows: Vec<Row> = mods.into_iter().map(|m| Row::new(m, registry)).collect();
+    match format {
+        Format::Markdown => markdown(&rows),
+        Format::Csv => csv(&rows),
+        Format::Json => {
+            let mut res = serde_json::to_string_pretty(&rows).expect("mod lists serialize to JSON");
+            res.push('\n');
+            res
+        }
+        Format::Html => html(&rows),
+    }
+}
+
+fn markdown(rows: &[Row]) -> String {
+    // `<` would start inline HTML
+    let cell = |s: &str| {
+        s.replace('\\', "\\\\")
+            .replace('|', "\\|")
+            .replace('<', "\\<")
+            .replace('\n', " ")
+    };
+    let mut res = String::from(
+        "| Name | packageId | Authors | Tags | Source | Workshop |\n\
+         | --- | --- | --- | --- | --- | --- |\n",
+    );
+    for row in rows {
+        let workshop = row
+            .workshop_url
+            .as_ref()
+            .map(|url| format!("[link]({url})"))
+            .unwrap_or_default();
+        let _ = writeln!(
+            res,
+            "| {} | `{}` | {} | {} | {} | {workshop} |",
+            cell(row.name),
+            row.package_id,
+            cell(&row.authors.join(", ")),
+            cell(&row.tag_names()),
+            row.source,
+        );
+    }
+    res
+}
+
+fn csv(rows: &[Row]) -> String {
+    let field = |s: &str| {
+        if s.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s.to_owned()
+        }
+    };
+    let mut res = String::from("name,packageId,authors,tags,source,workshop\n");
+    for row in rows {
+        let fields = [
+            field(row.name),
+            field(row.package_id),
+            field(&row.authors.join(", ")),
+            field(&row.tag_names()),
+            field(row.source),
+            field(row.workshop_url.as_deref().unwrap_or_default()),
+        ];
+        res.push_str(&fields.join(","));
+        res.push('\n');
+    }
+    res
+}
+
+fn html(rows: &[Row]) -> String {
+    let mut res = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Mod list</title>\n\
+         <style>\n\
+         table { border-collapse: collapse; }\n\
+         th, td { padding: 4px 8px; border: 1px solid #c6c6c6; text-align: left; }\n\
+         .tag { padding: 0 6px; margin-right: 4px; border-radius: 4px; }\n\
+         </style>\n</head>\n<body>\n<table>\n\
+         <tr><th>Name</th><th>packageId</th><th>Authors</th><th>Tags</th><th>Source</th><th>Workshop</th></tr>\n",
+    );
+    for row in rows {
+        let tags: String = row
+            .tags
+            .iter()
+            .map(|tag| {
+                format!(
+                    "<span class=\"tag\" style=\"background: {}\">{}</span>",
+                    tag.color,
+                    escape_html(tag.name)
+                )
+            })
+            .collect();
+        let workshop = row
+            .workshop_url
+            .as_ref()
+            .map(|url| format!("<a href=\"{0}\">{0}</a>", escape_html(url)))
+            .unwrap_or_default();
+        let _ = writeln!(
+            res,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{tags}</td><td>{}</td><td>{workshop}</td></tr>",
+            escape_html(row.name),
+            escape_html(row.package_id),
+            escape_html(&row.authors.join(", ")),
+            row.source,
+        );
+    }
+    res.push_str("</table>\n</body>\n</html>\n");
+    res
+}
+
+fn escape_html(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+/// `#rrggbb` of `color`, named and indexed colors take the values of the
+/// xterm palette. The default color is left to the page.
+fn css_color(color: Color) -> String {
+    const ANSI: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0x80, 0x00, 0x00),
+        (0x00, 0x80, 0x00),
+        (0x80, 0x80, 0x00),
+        (0x00, 0x00, 0x80),
+        (0x80, 0x00, 0x80),
+        (0x00, 0x80, 0x80),
+        (0xc0, 0xc0, 0xc0),
+        (0x80, 0x80, 0x80),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x00, 0x00, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    let index = match color {
+        Color::Reset => return "inherit".to_owned(),
+        Color::Rgb(r, g, b) => return format!("#{r:02x}{g:02x}{b:02x}"),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(index) => index,
+    };
+    let (r, g, b) = match index {
+        0..16 => ANSI[usize::from(index)],
+        // 6x6x6 color cube
+        16..232 => {
+            let level = |i: u8| if i == 0 { 0 } else { 55 + i * 40 };
+            let i = index - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        // grayscale ramp
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    };
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::{scan::Source, test_util::installed_mod};
+
+    /// One workshop mod whose name, author and tag need escaping.
+    fn export_tricky(format: Format) -> String {
+        let mut mods: OrderedItems<Mod> = vec![installed_mod(
+            "A | \"B\", &amp; &lt;C&gt;",
+            "a.b",
+            "<authors><li>Kiri, W</li><li>Oskar</li></authors>",
+            Source::Workshop,
+            "294100/123",
+        )]
+        .into();
+        let tag = Tag {
+            name: "<b>".to_owned(),
+            score: 0,
+            color: Color::LightRed,
+        };
+        mods.upsert_tag_to(&["a.b".to_owned()].into(), &tag);
+        export(format, mods.iter(), &vec![tag].into())
+    }
+
+    #[test]
+    fn markdown_cells_are_escaped() {
+        let md = export_tricky(Format::Markdown);
+        assert_eq!(
+            md.lines().nth(2),
+            Some(
+                "| A \\| \"B\", & \\<C> | `a.b` | Kiri, W, Oskar | \\<b> | Steam \
+                 | [link](https://steamcommunity.com/sharedfiles/filedetails/?id=123) |"
+            )
+        );
+    }
+
+    #[test]
+    fn csv_fields_are_quoted() {
+        let csv = export_tricky(Format::Csv);
+        assert_eq!(
+            csv.lines().nth(1),
+            Some(
+                "\"A | \"\"B\"\", & <C>\",a.b,\"Kiri, W, Oskar\",<b>,Steam,\
+                 https://steamcommunity.com/sharedfiles/filedetails/?id=123"
+            )
+        );
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let html = export_tricky(Format::Html);
+        assert!(html.contains(
+            "<tr><td>A | &quot;B&quot;, &amp; &lt;C&gt;</td><td>a.b</td><td>Kiri, W, Oskar</td>\
+             <td><span class=\"tag\" style=\"background: #ff0000\">&lt;b&gt;</span></td>"
+        ));
+    }
+
+    #[test]
+    fn json_keeps_the_text() {
+        let json: serde_json::Value = serde_json::from_str(&export_tricky(Format::Json)).unwrap();
+        assert_eq!(json[0]["name"], "A | \"B\", & <C>");
+        assert_eq!(json[0]["tags"][0]["color"], "#ff0000");
+    }
+
+    #[test]
+    fn colors_are_css_hex() {
+        assert_eq!(css_color(Color::Rgb(1, 0xab, 0xff)), "#01abff");
+        assert_eq!(css_color(Color::DarkGray), "#808080");
+        assert_eq!(css_color(Color::Indexed(12)), css_color(Color::LightBlue));
+        assert_eq!(css_color(Color::Indexed(16)), "#000000");
+        assert_eq!(css_color(Color::Indexed(196)), "#ff0000");
+        assert_eq!(css_color(Color::Indexed(110)), "#87afd7");
+        assert_eq!(css_color(Color::Indexed(255)), "#eeeeee");
+    }
+}
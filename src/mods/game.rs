@@ -2,81 +2,537 @@
 //!
 //! Here are all the models that should map 1:1 to Rimworld's XML files
 //! All XML should map to a valid model and viceversa, but unused unnecessary
-//! fields may be skipped.
+//! fields may be skipped. Saves are too big to deserialize, [`SaveMeta`] is
+//! streamed out of them instead.
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufRead, BufReader},
+    marker::PhantomData,
+    path::Path,
+};
+
+use quick_xml::{Reader, events::Event};
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+use super::{Item, Mod};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct ModMetaData {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
     #[serde(
         default,
+        skip_serializing_if = "Vec::is_empty",
         serialize_with = "wrap_strings",
         deserialize_with = "unwrap_strings"
     )]
-    pub supported_versions: Vec<String>,
-    #[serde(default)]
-    pub mod_dependencies_by_version: ModDependencies,
+    pub authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
     #[serde(
         default,
         serialize_with = "wrap_strings",
         deserialize_with = "unwrap_strings"
     )]
-    pub load_after: Vec<String>,
-    pub description: String,
-    pub package_id: String,
-}
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
-pub struct ModDependencies {
+    pub supported_versions: Vec<String>,
     #[serde(
         default,
-        rename = "v1.0",
+        skip_serializing_if = "Vec::is_empty",
         serialize_with = "wrap_deps",
         deserialize_with = "unwrap_deps"
     )]
-    pub v1_0: Vec<Dependency>,
+    pub mod_dependencies: Vec<Dependency>,
     #[serde(
         default,
-        rename = "v1.1",
-        serialize_with = "wrap_deps",
-        deserialize_with = "unwrap_deps"
+        skip_serializing_if = "ByVersion::is_empty",
+        serialize_with = "wrap_versioned_deps",
+        deserialize_with = "unwrap_versioned_deps"
     )]
-    pub v1_1: Vec<Dependency>,
+    pub mod_dependencies_by_version: ByVersion<Vec<Dependency>>,
     #[serde(
         default,
-        rename = "v1.2",
-        serialize_with = "wrap_deps",
-        deserialize_with = "unwrap_deps"
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "wrap_strings",
+        deserialize_with = "unwrap_strings"
     )]
-    pub v1_2: Vec<Dependency>,
+    pub load_after: Vec<String>,
     #[serde(
         default,
-        rename = "v1.3",
-        serialize_with = "wrap_deps",
-        deserialize_with = "unwrap_deps"
+        skip_serializing_if = "ByVersion::is_empty",
+        serialize_with = "wrap_versioned_strings",
+        deserialize_with = "unwrap_versioned_strings"
     )]
-    pub v1_3: Vec<Dependency>,
+    pub load_after_by_version: ByVersion<Vec<String>>,
     #[serde(
         default,
-        rename = "v1.4",
-        serialize_with = "wrap_deps",
-        deserialize_with = "unwrap_deps"
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "wrap_strings",
+        deserialize_with = "unwrap_strings"
     )]
-    pub v1_4: Vec<Dependency>,
+    pub load_before: Vec<String>,
     #[serde(
         default,
-        rename = "v1.5",
-        serialize_with = "wrap_deps",
-        deserialize_with = "unwrap_deps"
+        skip_serializing_if = "ByVersion::is_empty",
+        serialize_with = "wrap_versioned_strings",
+        deserialize_with = "unwrap_versioned_strings"
+    )]
+    pub load_before_by_version: ByVersion<Vec<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "wrap_strings",
+        deserialize_with = "unwrap_strings"
+    )]
+    pub force_load_after: Vec<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "wrap_strings",
+        deserialize_with = "unwrap_strings"
+    )]
+    pub force_load_before: Vec<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "wrap_strings",
+        deserialize_with = "unwrap_strings"
+    )]
+    pub incompatible_with: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "ByVersion::is_empty")]
+    pub descriptions_by_version: ByVersion<String>,
+    /// Empty when missing, such mods are rejected by [`super::scan`].
+    #[serde(default)]
+    pub package_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steam_app_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mod_icon_path: Option<String>,
+}
+impl ModMetaData {
+    /// `author` followed by every entry of `authors`.
+    pub fn author_names(&self) -> impl Iterator<Item = &str> {
+        self.author
+            .iter()
+            .chain(self.authors.iter())
+            .map(String::as_str)
+    }
+    /// Dependencies for `version`, of every version if `None`.
+    pub fn dependencies_for(&self, version: Option<&str>) -> Vec<&Dependency> {
+        versioned(
+            &self.mod_dependencies,
+            &self.mod_dependencies_by_version,
+            version,
+            |d| &d.package_id,
+        )
+    }
+    /// `loadAfter` for `version`, of every version if `None`.
+    pub fn load_after_for(&self, version: Option<&str>) -> Vec<&String> {
+        versioned(
+            &self.load_after,
+            &self.load_after_by_version,
+            version,
+            |s| s,
+        )
+    }
+    /// `loadBefore` for `version`, of every version if `None`.
+    pub fn load_before_for(&self, version: Option<&str>) -> Vec<&String> {
+        versioned(
+            &self.load_before,
+            &self.load_before_by_version,
+            version,
+            |s| s,
+        )
+    }
+    pub fn description_for(&self, version: Option<&str>) -> &str {
+        version
+            .and_then(|v| self.descriptions_by_version.get(v))
+            .unwrap_or(&self.description)
+    }
+}
+/// The list of `version` in `by_version` or `base` if there is none.
+///
+/// Without a version every list is merged, entries with the same
+/// case insensitive `id` are only returned once.
+fn versioned<'a, T>(
+    base: &'a [T],
+    by_version: &'a ByVersion<Vec<T>>,
+    version: Option<&str>,
+    id: fn(&T) -> &str,
+) -> Vec<&'a T> {
+    let lists: Vec<&[T]> = match version {
+        Some(version) => vec![by_version.get(version).map_or(base, Vec::as_slice)],
+        None => std::iter::once(base)
+            .chain(by_version.iter().map(|(_, list)| list.as_slice()))
+            .collect(),
+    };
+    let mut res: Vec<&T> = vec![];
+    for item in lists.into_iter().flatten() {
+        if !res.iter().any(|r| id(r).eq_ignore_ascii_case(id(item))) {
+            res.push(item);
+        }
+    }
+    res
+}
+/// `ModsConfig.xml`, the active load order the game reads on startup.
+///
+/// RimWorld stores every packageId in lowercase, lookups are therefore
+/// case insensitive.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModsConfigData {
+    #[serde(default)]
+    pub version: String,
+    #[serde(
+        default,
+        serialize_with = "wrap_strings",
+        deserialize_with = "unwrap_strings"
     )]
-    pub v1_5: Vec<Dependency>,
+    pub active_mods: Vec<String>,
+    #[serde(
+        default,
+        serialize_with = "wrap_strings",
+        deserialize_with = "unwrap_strings"
+    )]
+    pub known_expansions: Vec<String>,
+}
+impl ModsConfigData {
+    pub fn from_xml(xml: &str) -> Result<Self, quick_xml::DeError> {
+        quick_xml::de::from_str(xml)
+    }
+    pub fn to_xml(&self) -> Result<String, quick_xml::SeError> {
+        let mut buff = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        let mut ser = quick_xml::se::Serializer::new(&mut buff);
+        ser.indent(' ', 2);
+        self.serialize(ser)?;
+        buff.push('\n');
+        Ok(buff)
+    }
+    /// Game version without the build number, `"1.5.4104 rev435"` yields `"1.5"`.
+    pub fn game_version(&self) -> Option<&str> {
+        let version = self.version.split_whitespace().next()?;
+        let mut dots = version.match_indices('.').map(|(i, _)| i);
+        match (dots.next(), dots.next()) {
+            (Some(_), Some(end)) => Some(&version[..end]),
+            (Some(_), None) => Some(version),
+            _ => None,
+        }
+    }
+    /// Position of `package_id` in the load order, if active.
+    pub fn position(&self, package_id: &str) -> Option<usize> {
+        self.active_mods
+            .iter()
+            .position(|id| id.eq_ignore_ascii_case(package_id))
+    }
+    pub fn is_active(&self, package_id: &str) -> bool {
+        self.position(package_id).is_some()
+    }
+    /// Appends `package_id` at the end of the load order or removes it
+    /// if it was already active.
+    pub fn toggle(&mut self, package_id: &str) {
+        match self.position(package_id) {
+            Some(idx) => {
+                self.active_mods.remove(idx);
+            }
+            None => self.active_mods.push(package_id.to_ascii_lowercase()),
+        }
+    }
+    /// Appends `package_id` at the end of the load order or removes it.
+    pub fn set_active(&mut self, package_id: &str, active: bool) {
+        if self.is_active(package_id) != active {
+            self.toggle(package_id);
+        }
+    }
+    /// Moves the active mods among `package_ids` one slot up or down the
+    /// load order, consecutive ones move together.
+    pub fn shift(&mut self, package_ids: &[String], up: bool) {
+        let mut picked: Vec<bool> = self
+            .active_mods
+            .iter()
+            .map(|id| package_ids.iter().any(|p| p.eq_ignore_ascii_case(id)))
+            .collect();
+        let len = self.active_mods.len();
+        let pairs: Vec<usize> = if up {
+            (1..len).collect()
+        } else {
+            (1..len).rev().collect()
+        };
+        for idx in pairs {
+            let (from, to) = if up { (idx, idx - 1) } else { (idx - 1, idx) };
+            if picked[from] && !picked[to] {
+                self.active_mods.swap(idx - 1, idx);
+                picked.swap(idx - 1, idx);
+            }
+        }
+    }
+}
+
+/// Lists of a save's `meta` section, one entry per mod.
+const LISTS: [&[u8]; 3] = [b"modIds", b"modSteamIds", b"modNames"];
+/// Index in [`LISTS`] of the list an `<li>` at `path` belongs to.
+fn list_of(path: &[Vec<u8>]) -> Option<usize> {
+    match path {
+        [_, meta, field, li] if meta == b"meta" && li == b"li" => {
+            LISTS.iter().position(|list| list == field)
+        }
+        _ => None,
+    }
+}
+
+/// A mod listed in the `meta` section of a save.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedMod {
+    pub package_id: String,
+    /// `None` for mods that aren't on the workshop, saved as `0`.
+    pub steam_id: Option<String>,
+    pub name: String,
+}
+/// The `meta` section of a `.rws` save, the mods it was played with in order.
+///
+/// RimPy's mod lists are the same section under `<savedModList>`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SaveMeta {
+    pub game_version: String,
+    pub mods: Vec<SavedMod>,
+}
+impl SaveMeta {
+    /// Streams `reader` up to the end of `meta`, the rest of the save is
+    /// never read. `None` if there is no `meta` section.
+    pub fn from_reader(reader: impl BufRead) -> Result<Option<Self>, quick_xml::Error> {
+        let mut reader = Reader::from_reader(reader);
+        reader.config_mut().trim_text(true);
+        let mut buf = vec![];
+        let mut path: Vec<Vec<u8>> = vec![];
+        let mut found = false;
+        let mut version = None;
+        // entries of `LISTS`, empty ones included so they stay aligned
+        let mut lists: [Vec<String>; 3] = Default::default();
+        let mut item: Option<String> = None;
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) => {
+                    path.push(e.name().as_ref().to_vec());
+                    found |= path.len() == 2 && path[1] == b"meta";
+                    if list_of(&path).is_some() {
+                        item = Some(String::new());
+                    }
+                }
+                Event::End(_) => {
+                    if found && path.len() == 2 {
+                        break;
+                    }
+                    if let (Some(list), Some(text)) = (list_of(&path), item.take()) {
+                        lists[list].push(text);
+                    }
+                    path.pop();
+                }
+                Event::Empty(e) => {
+                    path.push(e.name().as_ref().to_vec());
+                    if let Some(list) = list_of(&path) {
+                        lists[list].push(String::new());
+                    }
+                    path.pop();
+                }
+                Event::Text(e) => {
+                    let text = e.unescape()?;
+                    match path.as_slice() {
+                        [_, meta, field] if meta == b"meta" && field == b"gameVersion" => {
+                            version = Some(text.into_owned());
+                        }
+                        _ => {
+                            if let Some(item) = &mut item {
+                                item.push_str(&text);
+                            }
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        if !found {
+            return Ok(None);
+        }
+        let [ids, steam_ids, names] = lists;
+        let mut steam_ids = steam_ids.into_iter();
+        let mut names = names.into_iter();
+        let mods = ids
+            .into_iter()
+            .map(|package_id| SavedMod {
+                steam_id: steam_ids.next().filter(|id| !id.is_empty() && id != "0"),
+                name: names
+                    .next()
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| package_id.clone()),
+                package_id,
+            })
+            .collect();
+        Ok(Some(Self {
+            game_version: version.unwrap_or_default(),
+            mods,
+        }))
+    }
+    pub fn open(path: &Path) -> Result<Option<Self>, quick_xml::Error> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+    pub fn active_mods(&self) -> Vec<String> {
+        self.mods.iter().map(|m| m.package_id.clone()).collect()
+    }
+    /// RimPy's `<savedModList>`.
+    pub fn to_xml(&self) -> Result<String, quick_xml::SeError> {
+        #[derive(Serialize)]
+        #[serde(rename = "savedModList")]
+        struct SavedModList<'a> {
+            meta: Meta<'a>,
+        }
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Meta<'a> {
+            game_version: &'a str,
+            #[serde(serialize_with = "wrap_strings")]
+            mod_ids: Vec<String>,
+            #[serde(serialize_with = "wrap_strings")]
+            mod_steam_ids: Vec<String>,
+            #[serde(serialize_with = "wrap_strings")]
+            mod_names: Vec<String>,
+        }
+        let list = SavedModList {
+            meta: Meta {
+                game_version: &self.game_version,
+                mod_ids: self.active_mods(),
+                mod_steam_ids: self
+                    .mods
+                    .iter()
+                    .map(|m| m.steam_id.clone().unwrap_or_else(|| "0".to_owned()))
+                    .collect(),
+                mod_names: self.mods.iter().map(|m| m.name.clone()).collect(),
+            },
+        };
+        let mut buff = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        let mut ser = quick_xml::se::Serializer::new(&mut buff);
+        ser.indent(' ', 2);
+        list.serialize(ser)?;
+        buff.push('\n');
+        Ok(buff)
+    }
+    /// Mods of the save that aren't among the installed `mods`.
+    pub fn missing<'a, 'b>(&'a self, mods: impl IntoIterator<Item = &'b Mod>) -> Vec<&'a SavedMod> {
+        let installed: HashSet<String> = mods
+            .into_iter()
+            .filter(|m| !m.missing)
+            .map(|m| m.identifier().to_ascii_lowercase())
+            .collect();
+        self.mods
+            .iter()
+            .filter(|m| !installed.contains(&m.package_id.to_ascii_lowercase()))
+            .collect()
+    }
+}
+
+/// Values keyed by game version, the `<v1.5>` element is stored as `"1.5"`.
+///
+/// Entries keep the order they were read in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ByVersion<T> {
+    entries: Vec<(String, T)>,
+}
+impl<T> Default for ByVersion<T> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+        }
+    }
+}
+impl<T> ByVersion<T> {
+    pub fn get(&self, version: &str) -> Option<&T> {
+        self.entries
+            .iter()
+            .find(|(v, _)| v == version)
+            .map(|(_, value)| value)
+    }
+    pub fn insert(&mut self, version: String, value: T) {
+        match self.entries.iter_mut().find(|(v, _)| *v == version) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((version, value)),
+        }
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.entries.iter().map(|(v, value)| (v.as_str(), value))
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    fn map<R>(self, f: impl Fn(T) -> R) -> ByVersion<R> {
+        ByVersion {
+            entries: self.entries.into_iter().map(|(v, t)| (v, f(t))).collect(),
+        }
+    }
+}
+impl<T: Serialize> Serialize for ByVersion<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(
+            self.entries
+                .iter()
+                .map(|(v, value)| (format!("v{v}"), value)),
+        )
+    }
+}
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for ByVersion<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = ByVersion<T>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("elements named after a game version")
+            }
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut res = ByVersion::default();
+                while let Some((version, value)) = map.next_entry::<String, T>()? {
+                    let version = version.strip_prefix('v').unwrap_or(&version);
+                    res.insert(version.to_owned(), value);
+                }
+                Ok(res)
+            }
+            fn visit_str<E>(self, _: &str) -> Result<Self::Value, E> {
+                Ok(ByVersion::default())
+            }
+            fn visit_unit<E>(self) -> Result<Self::Value, E> {
+                Ok(ByVersion::default())
+            }
+        }
+        // `deserialize_any` also accepts the struct like maps of older saves
+        deserializer.deserialize_any(Visitor(PhantomData))
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Dependency {
     pub package_id: String,
+    #[serde(default)]
     pub display_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steam_workshop_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
 }
 
 fn wrap_deps<S>(deps: &[Dependency], serializer: S) -> Result<S::Ok, S::Error>
@@ -85,6 +541,24 @@ where
 {
     wrap_list(deps, serializer)
 }
+fn wrap_versioned_deps<S>(
+    deps: &ByVersion<Vec<Dependency>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    wrap_versioned_list(deps, serializer)
+}
+fn wrap_versioned_strings<S>(
+    strings: &ByVersion<Vec<String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    wrap_versioned_list(strings, serializer)
+}
 fn wrap_strings<S>(deps: &[String], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -97,6 +571,18 @@ where
 {
     unwrap_list(deserializer)
 }
+fn unwrap_versioned_deps<'de, D>(deserializer: D) -> Result<ByVersion<Vec<Dependency>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    unwrap_versioned_list(deserializer)
+}
+fn unwrap_versioned_strings<'de, D>(deserializer: D) -> Result<ByVersion<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    unwrap_versioned_list(deserializer)
+}
 fn unwrap_strings<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -129,3 +615,315 @@ where
     }
     Ok(List::deserialize(deserializer)?.li)
 }
+#[inline]
+fn wrap_versioned_list<S, R>(
+    by_version: &ByVersion<Vec<R>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    R: Serialize,
+{
+    #[derive(Serialize)]
+    struct List<'a, R> {
+        li: &'a [R],
+    }
+
+    serializer.collect_map(
+        by_version
+            .iter()
+            .map(|(v, list)| (format!("v{v}"), List { li: list })),
+    )
+}
+#[inline]
+fn unwrap_versioned_list<'de, D, R>(deserializer: D) -> Result<ByVersion<Vec<R>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    R: Default + serde::Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    struct List<R> {
+        #[serde(default)]
+        li: Vec<R>,
+    }
+    Ok(ByVersion::<List<R>>::deserialize(deserializer)?.map(|l| l.li))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL_ABOUT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<ModMetaData>
+  <name>Example Mod</name>
+  <author>Oskar</author>
+  <authors>
+    <li>Oskar</li>
+    <li>Kiri</li>
+  </authors>
+  <modVersion>1.2.3</modVersion>
+  <url>https://example.com</url>
+  <supportedVersions>
+    <li>1.5</li>
+    <li>1.6</li>
+  </supportedVersions>
+  <modDependencies>
+    <li>
+      <packageId>brrainz.harmony</packageId>
+      <displayName>Harmony</displayName>
+      <steamWorkshopUrl>steam://url/CommunityFilePage/2009463077</steamWorkshopUrl>
+      <downloadUrl>https://github.com/pardeike/HarmonyRimWorld/releases/latest</downloadUrl>
+    </li>
+  </modDependencies>
+  <modDependenciesByVersion>
+    <v1.6>
+      <li>
+        <packageId>unlimitedhugs.hugslib</packageId>
+        <displayName>HugsLib</displayName>
+      </li>
+    </v1.6>
+  </modDependenciesByVersion>
+  <loadAfter>
+    <li>ludeon.rimworld</li>
+  </loadAfter>
+  <loadAfterByVersion>
+    <v1.5>
+      <li>ludeon.rimworld.royalty</li>
+    </v1.5>
+  </loadAfterByVersion>
+  <loadBefore>
+    <li>other.mod</li>
+  </loadBefore>
+  <loadBeforeByVersion>
+    <v1.6>
+      <li>another.mod</li>
+    </v1.6>
+  </loadBeforeByVersion>
+  <forceLoadAfter>
+    <li>forced.after</li>
+  </forceLoadAfter>
+  <forceLoadBefore>
+    <li>forced.before</li>
+  </forceLoadBefore>
+  <incompatibleWith>
+    <li>bad.mod</li>
+  </incompatibleWith>
+  <description>Does &lt;b&gt;things&lt;/b&gt;</description>
+  <descriptionsByVersion>
+    <v1.6>Does newer things</v1.6>
+  </descriptionsByVersion>
+  <packageId>Oskar.ExampleMod</packageId>
+  <steamAppId>123456789</steamAppId>
+  <modIconPath>UI/Icon</modIconPath>
+</ModMetaData>"#;
+
+    fn to_xml<T: Serialize>(value: &T) -> String {
+        let mut buff = String::new();
+        let mut ser = quick_xml::se::Serializer::new(&mut buff);
+        ser.indent(' ', 2);
+        value.serialize(ser).unwrap();
+        buff
+    }
+
+    #[test]
+    fn full_about_round_trips() {
+        let metadata: ModMetaData = quick_xml::de::from_str(FULL_ABOUT).unwrap();
+        assert_eq!(
+            metadata.author_names().collect::<Vec<_>>(),
+            ["Oskar", "Oskar", "Kiri"]
+        );
+        assert_eq!(metadata.mod_version.as_deref(), Some("1.2.3"));
+        assert_eq!(metadata.steam_app_id.as_deref(), Some("123456789"));
+        assert_eq!(metadata.incompatible_with, ["bad.mod"]);
+        assert_eq!(metadata.description_for(Some("1.6")), "Does newer things");
+        assert_eq!(metadata.description_for(Some("1.5")), "Does <b>things</b>");
+
+        let xml = to_xml(&metadata);
+        let again: ModMetaData = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(metadata, again);
+        assert_eq!(xml, to_xml(&again));
+    }
+
+    #[test]
+    fn versioned_lists_override_unversioned() {
+        let metadata: ModMetaData = quick_xml::de::from_str(FULL_ABOUT).unwrap();
+        let ids = |deps: Vec<&Dependency>| {
+            deps.into_iter()
+                .map(|d| d.package_id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(metadata.dependencies_for(Some("1.5"))),
+            ["brrainz.harmony"]
+        );
+        assert_eq!(
+            ids(metadata.dependencies_for(Some("1.6"))),
+            ["unlimitedhugs.hugslib"]
+        );
+        assert_eq!(
+            ids(metadata.dependencies_for(None)),
+            ["brrainz.harmony", "unlimitedhugs.hugslib"]
+        );
+        assert_eq!(
+            metadata.load_after_for(Some("1.5")),
+            ["ludeon.rimworld.royalty"]
+        );
+        assert_eq!(metadata.load_after_for(Some("1.6")), ["ludeon.rimworld"]);
+    }
+
+    #[test]
+    fn minimal_about_parses() {
+        let xml = r#"<ModMetaData>
+  <name>Old Mod</name>
+  <packageId>someone.oldmod</packageId>
+</ModMetaData>"#;
+        let metadata: ModMetaData = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(metadata.name, "Old Mod");
+        assert!(metadata.author.is_none());
+        assert!(metadata.mod_dependencies_by_version.is_empty());
+
+        // only the fields every About.xml has are written when empty
+        let written = to_xml(&metadata);
+        assert_eq!(
+            written,
+            r#"<ModMetaData>
+  <name>Old Mod</name>
+  <supportedVersions/>
+  <description/>
+  <packageId>someone.oldmod</packageId>
+</ModMetaData>"#
+        );
+        let again: ModMetaData = quick_xml::de::from_str(&written).unwrap();
+        assert_eq!(metadata, again);
+    }
+
+    #[test]
+    fn any_version_key_round_trips_in_order() {
+        let xml = r#"<ModMetaData>
+  <name>Versioned</name>
+  <packageId>someone.versioned</packageId>
+  <loadAfterByVersion>
+    <v1.6><li>six.mod</li></v1.6>
+    <v1.0><li>one.mod</li><li>other.mod</li></v1.0>
+    <v2.10><li>future.mod</li></v2.10>
+  </loadAfterByVersion>
+  <descriptionsByVersion>
+    <v1.7>Not released yet</v1.7>
+  </descriptionsByVersion>
+</ModMetaData>"#;
+        let metadata: ModMetaData = quick_xml::de::from_str(xml).unwrap();
+        let versions: Vec<&str> = metadata
+            .load_after_by_version
+            .iter()
+            .map(|(v, _)| v)
+            .collect();
+        assert_eq!(versions, ["1.6", "1.0", "2.10"]);
+        assert_eq!(
+            metadata.load_after_by_version.get("1.0").unwrap(),
+            &["one.mod", "other.mod"]
+        );
+        assert_eq!(metadata.load_after_for(Some("2.10")), ["future.mod"]);
+        assert_eq!(metadata.description_for(Some("1.7")), "Not released yet");
+
+        let again = to_xml(&metadata);
+        assert!(again.find("<v1.6>") < again.find("<v1.0>"));
+        assert!(again.find("<v1.0>") < again.find("<v2.10>"));
+        let again: ModMetaData = quick_xml::de::from_str(&again).unwrap();
+        assert_eq!(metadata, again);
+    }
+
+    #[test]
+    fn empty_save_entries_keep_the_lists_aligned() {
+        let rws = r#"<savegame>
+  <meta>
+    <modIds><li>local.one</li><li>local.two</li><li>steam.mod</li></modIds>
+    <modSteamIds><li/><li></li><li>123</li></modSteamIds>
+    <modNames><li>One</li><li></li><li>Steam</li></modNames>
+  </meta>
+</savegame>"#;
+        let meta = SaveMeta::from_reader(rws.as_bytes()).unwrap().unwrap();
+        let mods: Vec<_> = meta
+            .mods
+            .iter()
+            .map(|m| {
+                (
+                    m.package_id.as_str(),
+                    m.steam_id.as_deref(),
+                    m.name.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            mods,
+            [
+                ("local.one", None, "One"),
+                ("local.two", None, "local.two"),
+                ("steam.mod", Some("123"), "Steam"),
+            ]
+        );
+    }
+
+    #[test]
+    fn save_meta_stops_at_game() {
+        let rws = r#"<?xml version="1.0" encoding="utf-8"?>
+<savegame>
+  <meta>
+    <gameVersion>1.5.4104 rev435</gameVersion>
+    <modIds>
+      <li>ludeon.rimworld</li>
+      <li>brrainz.harmony</li>
+    </modIds>
+    <modSteamIds>
+      <li>0</li>
+      <li>2009463077</li>
+    </modSteamIds>
+    <modNames>
+      <li>Core</li>
+      <li>Harmony &amp; co</li>
+    </modNames>
+  </meta>
+  <game><unclosed>
+"#;
+        let meta = SaveMeta::from_reader(rws.as_bytes()).unwrap().unwrap();
+        assert_eq!(meta.game_version, "1.5.4104 rev435");
+        assert_eq!(
+            meta.mods[1],
+            SavedMod {
+                package_id: "brrainz.harmony".to_owned(),
+                steam_id: Some("2009463077".to_owned()),
+                name: "Harmony & co".to_owned(),
+            }
+        );
+        assert_eq!(meta.mods[0].steam_id, None);
+        let list = meta.to_xml().unwrap();
+        assert!(list.contains("<savedModList>"));
+        assert_eq!(
+            SaveMeta::from_reader(list.as_bytes()).unwrap().as_ref(),
+            Some(&meta)
+        );
+        assert_eq!(
+            SaveMeta::from_reader("<savegame><game/></savegame>".as_bytes()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn mods_config_round_trips() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<ModsConfigData>
+  <version>1.5.4104 rev435</version>
+  <activeMods>
+    <li>brrainz.harmony</li>
+    <li>ludeon.rimworld</li>
+  </activeMods>
+  <knownExpansions>
+    <li>ludeon.rimworld.royalty</li>
+  </knownExpansions>
+</ModsConfigData>
+"#;
+        let config = ModsConfigData::from_xml(xml).unwrap();
+        assert_eq!(config.game_version(), Some("1.5"));
+        assert_eq!(config.position("Ludeon.RimWorld"), Some(1));
+        assert_eq!(config.to_xml().unwrap(), xml);
+    }
+}
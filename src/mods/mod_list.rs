@@ -0,0 +1,243 @@
+//! Load orders exchanged with other mod managers
+//!
+//! RimSort reads and writes lists shaped like `ModsConfig.xml`, RimPy wraps
+//! the `meta` section of a save in `<savedModList>`. [`read`] takes either,
+//! entries are matched to installed mods by packageId and keep their order.
+use std::{collections::HashMap, str::FromStr};
+
+use super::{
+    Item, Mod, OrderedItems,
+    game::{ModsConfigData, SaveMeta, SavedMod},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListFormat {
+    RimSort,
+    RimPy,
+}
+impl ListFormat {
+    pub const ALL: [ListFormat; 2] = [ListFormat::RimSort, ListFormat::RimPy];
+    pub fn str_repr(&self) -> &'static str {
+        match self {
+            ListFormat::RimSort => "RimSort",
+            ListFormat::RimPy => "RimPy",
+        }
+    }
+}
+impl FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ListFormat::ALL
+            .into_iter()
+            .find(|f| s.eq_ignore_ascii_case(f.str_repr()))
+            .ok_or_else(|| format!("Unknown mod list format {s}, expected rimsort or rimpy"))
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadError {
+    Xml(quick_xml::Error),
+    De(quick_xml::DeError),
+}
+impl std::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Xml(e) => write!(f, "{e}"),
+            ReadError::De(e) => write!(f, "{e}"),
+        }
+    }
+}
+impl std::error::Error for ReadError {}
+
+/// An imported load order.
+#[derive(Clone, Debug, Default)]
+pub struct ModList {
+    /// Every entry in order, spelled like the installed mod when there is one.
+    pub active_mods: Vec<String>,
+    /// Entries that aren't installed, they are kept in `active_mods`.
+    pub unknown: Vec<String>,
+}
+
+/// Reads a list in any [`ListFormat`] and matches it to the installed `mods`.
+pub fn read(xml: &str, mods: &OrderedItems<Mod>) -> Result<ModList, ReadError> {
+    let ids = match SaveMeta::from_reader(xml.as_bytes()).map_err(ReadError::Xml)? {
+        Some(meta) => meta.active_mods(),
+        None => {
+            ModsConfigData::from_xml(xml)
+                .map_err(ReadError::De)?
+                .active_mods
+        }
+    };
+    let installed = installed(mods);
+    let mut res = ModList::default();
+    for id in ids {
+        match installed.get(&id.to_ascii_lowercase()) {
+            Some(game_mod) => res.active_mods.push(game_mod.identifier().to_owned()),
+            None => {
+                res.unknown.push(id.clone());
+                res.active_mods.push(id);
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// `active_mods` in `format`, names and workshop ids come from the installed
+/// `mods`. The version and expansions are taken from `config`.
+pub fn write(
+    format: ListFormat,
+    active_mods: &[String],
+    mods: &OrderedItems<Mod>,
+    config: &ModsConfigData,
+) -> Result<String, quick_xml::SeError> {
+    match format {
+        ListFormat::RimSort => ModsConfigData {
+            active_mods: active_mods.to_vec(),
+            ..config.clone()
+        }
+        .to_xml(),
+        ListFormat::RimPy => {
+            let installed = installed(mods);
+            let mods = active_mods
+                .iter()
+                .map(|id| {
+                    let game_mod = installed.get(&id.to_ascii_lowercase());
+                    SavedMod {
+                        package_id: id.clone(),
+                        steam_id: game_mod.and_then(|m| m.workshop_id()).map(str::to_owned),
+                        name: game_mod.map_or_else(|| id.clone(), |m| m.metadata.name.clone()),
+                    }
+                })
+                .collect();
+            SaveMeta {
+                game_version: config.version.clone(),
+                mods,
+            }
+            .to_xml()
+        }
+    }
+}
+
+/// Installed mods by lowercase packageId.
+fn installed(mods: &OrderedItems<Mod>) -> HashMap<String, &Mod> {
+    mods.iter()
+        .filter(|m| !m.missing)
+        .map(|m| (m.identifier().to_ascii_lowercase(), m))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::{scan::Source, test_util::installed_mod};
+
+    const RIMSORT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<ModsConfigData>
+  <version>1.5.4104 rev435</version>
+  <activeMods>
+    <li>ludeon.rimworld</li>
+    <li>brrainz.harmony</li>
+    <li>gone.mod</li>
+    <li>old.mod</li>
+  </activeMods>
+  <knownExpansions>
+    <li>ludeon.rimworld.royalty</li>
+  </knownExpansions>
+</ModsConfigData>
+"#;
+    const RIMPY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<savedModList>
+  <meta>
+    <gameVersion>1.5.4104 rev435</gameVersion>
+    <modIds>
+      <li>ludeon.rimworld</li>
+      <li>brrainz.harmony</li>
+      <li>gone.mod</li>
+      <li>old.mod</li>
+    </modIds>
+    <modSteamIds>
+      <li>0</li>
+      <li>2009463077</li>
+      <li>0</li>
+      <li>0</li>
+    </modSteamIds>
+    <modNames>
+      <li>Core</li>
+      <li>Harmony</li>
+      <li>gone.mod</li>
+      <li>old.mod</li>
+    </modNames>
+  </meta>
+</savedModList>
+"#;
+
+    /// Core, Harmony from the workshop and a mod that was uninstalled.
+    fn installed_mods() -> OrderedItems<Mod> {
+        let game_mod =
+            |name, package_id, source, path| installed_mod(name, package_id, "", source, path);
+        let mut old = game_mod("Old", "old.mod", Source::Local, "Mods/Old");
+        old.missing = true;
+        vec![
+            game_mod("Core", "Ludeon.RimWorld", Source::Game, "Data/Core"),
+            game_mod(
+                "Harmony",
+                "brrainz.harmony",
+                Source::Workshop,
+                "294100/2009463077",
+            ),
+            old,
+        ]
+        .into()
+    }
+
+    #[test]
+    fn both_formats_read_alike() {
+        let mods = installed_mods();
+        for xml in [RIMSORT, RIMPY] {
+            let list = read(xml, &mods).unwrap();
+            assert_eq!(
+                list.active_mods,
+                ["Ludeon.RimWorld", "brrainz.harmony", "gone.mod", "old.mod"]
+            );
+            assert_eq!(list.unknown, ["gone.mod", "old.mod"]);
+        }
+    }
+
+    #[test]
+    fn lists_round_trip() {
+        let mods = installed_mods();
+        let config = ModsConfigData::from_xml(RIMSORT).unwrap();
+        let active_mods: Vec<String> =
+            ["ludeon.rimworld", "brrainz.harmony", "gone.mod", "old.mod"]
+                .map(str::to_owned)
+                .into();
+        assert_eq!(
+            write(ListFormat::RimSort, &active_mods, &mods, &config).unwrap(),
+            RIMSORT
+        );
+        let rimpy = write(ListFormat::RimPy, &active_mods, &mods, &config).unwrap();
+        // Core isn't installed as a workshop item, and the names of mods that
+        // aren't installed fall back to their packageIds
+        assert_eq!(
+            SaveMeta::from_reader(rimpy.as_bytes()).unwrap(),
+            SaveMeta::from_reader(RIMPY.as_bytes()).unwrap()
+        );
+        assert_eq!(
+            read(&rimpy, &mods).unwrap().unknown,
+            ["gone.mod", "old.mod"]
+        );
+    }
+
+    #[test]
+    fn other_files_are_errors() {
+        let mods = installed_mods();
+        assert!(matches!(read("<a><b></a>", &mods), Err(ReadError::Xml(_))));
+        assert!(read("<ModsConfigData><activeMods><li>", &mods).is_err());
+        assert_eq!("RimPy".parse(), Ok(ListFormat::RimPy));
+        assert_eq!(
+            "rimworld".parse::<ListFormat>(),
+            Err("Unknown mod list format rimworld, expected rimsort or rimpy".to_owned())
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use super::Item;
+
+/// A named load order, the active mods of `ModsConfig.xml` in order.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Profile {
+    pub name: String,
+    pub active_mods: Vec<String>,
+}
+impl Item for Profile {
+    fn identifier(&self) -> &str {
+        &self.name
+    }
+
+    fn patch(&mut self, other: Self) {
+        self.active_mods = other.active_mods;
+    }
+
+    fn vec_order(&self, other: &Self) -> Ordering {
+        self.name.cmp(&other.name)
+    }
+}
@@ -0,0 +1,262 @@
+//! Discovery of installed mods
+//!
+//! Every folder of a [`ModSource`] is expected to be a mod with an
+//! `About/About.xml`. Folders that can't be loaded don't stop the scan,
+//! they end up as a [`ScanError`] in the [`ScanReport`].
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{Item, Mod, game::ModMetaData};
+
+/// Where RimWorld finds a mod.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Source {
+    /// The game's `Data/` folder, Core and DLCs.
+    Game,
+    /// The game's `Mods/` folder.
+    #[default]
+    Local,
+    /// `steamapps/workshop/content/294100`
+    Workshop,
+}
+impl Source {
+    pub fn str_repr(&self) -> &'static str {
+        match self {
+            Source::Game => "Game",
+            Source::Local => "Local",
+            Source::Workshop => "Steam",
+        }
+    }
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ModSource {
+    pub source: Source,
+    pub path: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum ScanErrorKind {
+    UnreadableFolder(std::io::Error),
+    MissingAbout(std::io::Error),
+    BadXml(quick_xml::DeError),
+    MissingPackageId,
+    /// Another folder, `first`, already provides this packageId.
+    DuplicatePackageId {
+        package_id: String,
+        first: PathBuf,
+    },
+}
+/// A folder that couldn't be loaded as a mod.
+#[derive(Debug)]
+pub struct ScanError {
+    pub path: PathBuf,
+    pub kind: ScanErrorKind,
+}
+impl fmt::Display for ScanErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanErrorKind::UnreadableFolder(e) => write!(f, "Couldn't read folder: {e}"),
+            ScanErrorKind::MissingAbout(e) => write!(f, "Couldn't read About/About.xml: {e}"),
+            ScanErrorKind::BadXml(e) => write!(f, "Malformed About/About.xml: {e}"),
+            ScanErrorKind::MissingPackageId => write!(f, "About/About.xml has no packageId"),
+            ScanErrorKind::DuplicatePackageId { package_id, first } => {
+                write!(f, "{package_id} is already provided by {}", first.display())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ScanReport {
+    pub mods: Vec<Mod>,
+    pub errors: Vec<ScanError>,
+}
+
+/// Reads every mod of every source, in order.
+///
+/// Sources that don't exist are skipped, when a packageId is found more than
+/// once the first one is kept.
+pub fn scan_sources(sources: &[ModSource]) -> ScanReport {
+    let mut report = ScanReport::default();
+    for source in sources {
+        if !source.path.is_dir() {
+            warn!("Skipping missing mod source {}", source.path.display());
+            continue;
+        }
+        scan_dir(&source.path, source.source, &mut report);
+    }
+
+    let mut seen: HashMap<String, PathBuf> = HashMap::new();
+    let mut errors = vec![];
+    report.mods.retain(|game_mod| {
+        let id = game_mod.identifier().to_ascii_lowercase();
+        match seen.get(&id) {
+            Some(first) => {
+                errors.push(ScanError {
+                    path: game_mod.path.clone(),
+                    kind: ScanErrorKind::DuplicatePackageId {
+                        package_id: game_mod.identifier().to_owned(),
+                        first: first.clone(),
+                    },
+                });
+                false
+            }
+            None => {
+                seen.insert(id, game_mod.path.clone());
+                true
+            }
+        }
+    });
+    report.errors.extend(errors);
+    report
+}
+/// Reads the metadata of every mod inside `path` into `report`.
+pub fn scan_dir(path: &Path, source: Source, report: &mut ScanReport) {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            report.errors.push(ScanError {
+                path: path.to_owned(),
+                kind: ScanErrorKind::UnreadableFolder(e),
+            });
+            return;
+        }
+    };
+    // read_dir's order is up to the platform, sorted paths make it clear
+    // which of two folders with the same packageId is kept
+    let mut paths = vec![];
+    for entry in entries {
+        match entry {
+            Ok(entry) => paths.push(entry.path()),
+            Err(e) => report.errors.push(ScanError {
+                path: path.to_owned(),
+                kind: ScanErrorKind::UnreadableFolder(e),
+            }),
+        }
+    }
+    paths.sort();
+    for path in paths {
+        if !path.is_dir() {
+            continue;
+        }
+        match read_metadata(&path) {
+            Ok(metadata) => report.mods.push(Mod::new(metadata, source, path)),
+            Err(kind) => report.errors.push(ScanError { path, kind }),
+        }
+    }
+}
+fn read_metadata(path: &Path) -> Result<ModMetaData, ScanErrorKind> {
+    let xml = std::fs::read_to_string(path.join("About/About.xml"))
+        .map_err(ScanErrorKind::MissingAbout)?;
+    let metadata: ModMetaData = quick_xml::de::from_str(&xml).map_err(ScanErrorKind::BadXml)?;
+    if metadata.package_id.trim().is_empty() {
+        return Err(ScanErrorKind::MissingPackageId);
+    }
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh folder under the system's temp dir, removed when dropped.
+    struct TempDir(PathBuf);
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("r2m2-{}-{name}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+        fn add_mod(&self, folder: &str, about: Option<&str>) {
+            let about_dir = self.0.join(folder).join("About");
+            std::fs::create_dir_all(&about_dir).unwrap();
+            if let Some(about) = about {
+                std::fs::write(about_dir.join("About.xml"), about).unwrap();
+            }
+        }
+    }
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn about(package_id: &str) -> String {
+        format!(
+            "<ModMetaData><name>{package_id}</name><packageId>{package_id}</packageId></ModMetaData>"
+        )
+    }
+    fn scan(dir: &TempDir) -> ScanReport {
+        scan_sources(&[ModSource {
+            source: Source::Local,
+            path: dir.0.clone(),
+        }])
+    }
+    fn error_of<'a>(report: &'a ScanReport, folder: &str) -> &'a ScanErrorKind {
+        let error = report
+            .errors
+            .iter()
+            .find(|e| e.path.ends_with(folder))
+            .unwrap_or_else(|| panic!("no error for {folder}"));
+        &error.kind
+    }
+
+    #[test]
+    fn broken_folders_are_reported() {
+        let dir = TempDir::new("broken");
+        dir.add_mod("good", Some(&about("a.a")));
+        dir.add_mod("no_about", None);
+        dir.add_mod("bad_xml", Some("<ModMetaData><name>Broken</ModMetaData>"));
+        dir.add_mod(
+            "no_id",
+            Some("<ModMetaData><name>No id</name></ModMetaData>"),
+        );
+        std::fs::write(dir.0.join("readme.txt"), "not a mod").unwrap();
+
+        let report = scan(&dir);
+        let ids: Vec<&str> = report.mods.iter().map(|m| m.identifier()).collect();
+        assert_eq!(ids, ["a.a"]);
+        assert_eq!(report.errors.len(), 3);
+        assert!(matches!(
+            error_of(&report, "no_about"),
+            ScanErrorKind::MissingAbout(_)
+        ));
+        assert!(matches!(
+            error_of(&report, "bad_xml"),
+            ScanErrorKind::BadXml(_)
+        ));
+        assert!(matches!(
+            error_of(&report, "no_id"),
+            ScanErrorKind::MissingPackageId
+        ));
+    }
+
+    #[test]
+    fn first_folder_of_a_duplicate_package_id_is_kept() {
+        let dir = TempDir::new("duplicate");
+        // created in reverse so read_dir's order is less likely to match
+        dir.add_mod("c_copy", Some(&about("Some.Mod")));
+        dir.add_mod("b_copy", Some(&about("some.mod")));
+        dir.add_mod("a_original", Some(&about("some.mod")));
+
+        let report = scan(&dir);
+        assert_eq!(report.mods.len(), 1);
+        assert!(report.mods[0].path.ends_with("a_original"));
+        assert_eq!(report.errors.len(), 2);
+        for folder in ["b_copy", "c_copy"] {
+            match error_of(&report, folder) {
+                ScanErrorKind::DuplicatePackageId { first, .. } => {
+                    assert!(first.ends_with("a_original"))
+                }
+                other => panic!("{folder}: {other}"),
+            }
+        }
+    }
+}
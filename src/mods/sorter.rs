@@ -0,0 +1,542 @@
+//! Load order sorting
+//!
+//! [`RuleGraph`] gathers every ordering rule declared by the active mods'
+//! [`ModMetaData`](super::game::ModMetaData) and [`RuleGraph::sort`] turns
+//! them into a load order. Mods without rules between them keep the order of
+//! [`OrderedItems<Mod>`], official content always goes first.
+//! Contradicting rules are reported as [`Cycle`]s, [`RuleGraph::sort`] breaks
+//! them by ignoring the weakest rules involved.
+use std::{cmp::Reverse, collections::BinaryHeap, collections::HashMap, fmt};
+
+use super::{Item, Mod, OrderedItems, game::ModsConfigData};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleKind {
+    LoadAfter,
+    LoadBefore,
+    ForceLoadAfter,
+    ForceLoadBefore,
+    Dependency,
+}
+impl RuleKind {
+    pub fn str_repr(&self) -> &'static str {
+        match self {
+            RuleKind::LoadAfter => "loadAfter",
+            RuleKind::LoadBefore => "loadBefore",
+            RuleKind::ForceLoadAfter => "forceLoadAfter",
+            RuleKind::ForceLoadBefore => "forceLoadBefore",
+            RuleKind::Dependency => "modDependencies",
+        }
+    }
+    /// Rules with lower strength are ignored first when breaking cycles.
+    fn strength(&self) -> u8 {
+        match self {
+            RuleKind::LoadAfter | RuleKind::LoadBefore => 0,
+            RuleKind::ForceLoadAfter | RuleKind::ForceLoadBefore => 1,
+            RuleKind::Dependency => 2,
+        }
+    }
+}
+
+/// `before` has to be loaded before `after` because `declared_by` says so.
+///
+/// All three are node indices of the [`RuleGraph`] the rule belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub kind: RuleKind,
+    pub declared_by: usize,
+    pub before: usize,
+    pub after: usize,
+}
+
+impl Rule {
+    /// The mod named in the declaring mod's About.xml.
+    pub fn target(&self) -> usize {
+        if self.declared_by == self.before {
+            self.after
+        } else {
+            self.before
+        }
+    }
+}
+
+/// A strongly connected component of the rules, none of its mods can be
+/// loaded while respecting all of them.
+#[derive(Clone, Debug)]
+pub struct Cycle {
+    pub package_ids: Vec<String>,
+    pub rules: Vec<CycleRule>,
+}
+/// A [`Rule`] detached from its graph.
+#[derive(Clone, Debug)]
+pub struct CycleRule {
+    pub declared_by: String,
+    pub kind: RuleKind,
+    pub target: String,
+}
+impl fmt::Display for CycleRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.declared_by,
+            self.kind.str_repr(),
+            self.target
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RuleGraph {
+    /// Lowercase packageIds, in the order of [`OrderedItems<Mod>`].
+    ids: Vec<String>,
+    rules: Vec<Rule>,
+}
+impl RuleGraph {
+    /// Builds the graph of the installed mods that are active in `config`.
+    ///
+    /// Rules pointing to mods outside of the graph are ignored.
+    pub fn new(mods: &OrderedItems<Mod>, config: &ModsConfigData) -> Self {
+        let nodes: Vec<&Mod> = mods
+            .iter()
+            .filter(|m| !m.missing && config.is_active(m.identifier()))
+            .collect();
+
+        let ids: Vec<String> = nodes
+            .iter()
+            .map(|m| m.identifier().to_ascii_lowercase())
+            .collect();
+        let index: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), idx))
+            .collect();
+
+        let game_version = config.game_version();
+        let mut rules = vec![];
+        for (node, game_mod) in nodes.iter().enumerate() {
+            let metadata = &game_mod.metadata;
+            let deps = metadata.dependencies_for(game_version);
+            let declared = [
+                (RuleKind::LoadAfter, metadata.load_after_for(game_version)),
+                (RuleKind::LoadBefore, metadata.load_before_for(game_version)),
+                (
+                    RuleKind::ForceLoadAfter,
+                    metadata.force_load_after.iter().collect(),
+                ),
+                (
+                    RuleKind::ForceLoadBefore,
+                    metadata.force_load_before.iter().collect(),
+                ),
+                (
+                    RuleKind::Dependency,
+                    deps.iter().map(|d| &d.package_id).collect::<Vec<_>>(),
+                ),
+            ];
+            for (kind, targets) in declared {
+                for target in targets {
+                    let Some(&other) = index.get(target.to_ascii_lowercase().as_str()) else {
+                        continue;
+                    };
+                    if other == node {
+                        continue;
+                    }
+                    let (before, after) = match kind {
+                        RuleKind::LoadBefore | RuleKind::ForceLoadBefore => (node, other),
+                        _ => (other, node),
+                    };
+                    rules.push(Rule {
+                        kind,
+                        declared_by: node,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        Self { ids, rules }
+    }
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+    pub fn package_id(&self, node: usize) -> &str {
+        &self.ids[node]
+    }
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Every cycle in the rules, ordered by their lowest node.
+    pub fn cycles(&self) -> Vec<Cycle> {
+        strongly_connected(self.len(), &self.rules)
+            .into_iter()
+            .map(|component| Cycle {
+                package_ids: component.iter().map(|&n| self.ids[n].clone()).collect(),
+                rules: self
+                    .rules
+                    .iter()
+                    .filter(|r| component.contains(&r.before) && component.contains(&r.after))
+                    .map(|r| CycleRule {
+                        declared_by: self.ids[r.declared_by].clone(),
+                        kind: r.kind,
+                        target: self.ids[r.target()].clone(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+    /// Rules left after breaking every cycle.
+    ///
+    /// For each cycle all of its weakest rules are dropped, until none is left.
+    fn acyclic_rules(&self) -> Vec<Rule> {
+        let mut rules = self.rules.clone();
+        loop {
+            let components = strongly_connected(self.len(), &rules);
+            if components.is_empty() {
+                return rules;
+            }
+            for component in components {
+                let inside =
+                    |r: &Rule| component.contains(&r.before) && component.contains(&r.after);
+                let Some(weakest) = rules
+                    .iter()
+                    .filter(|r| inside(r))
+                    .map(|r| r.kind.strength())
+                    .min()
+                else {
+                    continue;
+                };
+                rules.retain(|r| !(inside(r) && r.kind.strength() == weakest));
+            }
+        }
+    }
+
+    /// Tie-breaking rank of every node, lower loads first.
+    ///
+    /// Official content and whatever has to be loaded before it come first,
+    /// everything else keeps its node order.
+    fn ranks(&self) -> Vec<(bool, usize)> {
+        let mut early: Vec<bool> = self.ids.iter().map(|id| is_official(id)).collect();
+        let mut stack: Vec<usize> = (0..self.len()).filter(|&node| early[node]).collect();
+        while let Some(node) = stack.pop() {
+            for rule in self.rules.iter().filter(|r| r.after == node) {
+                if !early[rule.before] {
+                    early[rule.before] = true;
+                    stack.push(rule.before);
+                }
+            }
+        }
+        early
+            .into_iter()
+            .enumerate()
+            .map(|(node, early)| (!early, node))
+            .collect()
+    }
+
+    /// Topological order of the graph, ties are broken by rank.
+    ///
+    /// Cycles are broken first, see [`Self::cycles`].
+    pub fn sort(&self) -> Vec<String> {
+        let mut in_degree = vec![0usize; self.len()];
+        let mut outgoing = vec![vec![]; self.len()];
+        for rule in &self.acyclic_rules() {
+            in_degree[rule.after] += 1;
+            outgoing[rule.before].push(rule.after);
+        }
+
+        let ranks = self.ranks();
+        let mut visited = vec![false; self.len()];
+        let mut ready: BinaryHeap<_> = (0..self.len())
+            .filter(|&node| in_degree[node] == 0)
+            .map(|node| Reverse(ranks[node]))
+            .collect();
+        let mut order = Vec::with_capacity(self.len());
+        while order.len() < self.len() {
+            let node = match ready.pop() {
+                Some(Reverse((_, node))) => node,
+                None => (0..self.len())
+                    .filter(|&node| !visited[node])
+                    .min_by_key(|&node| ranks[node])
+                    .unwrap(),
+            };
+            if visited[node] {
+                continue;
+            }
+            visited[node] = true;
+            order.push(self.ids[node].clone());
+            for &next in &outgoing[node] {
+                in_degree[next] = in_degree[next].saturating_sub(1);
+                if in_degree[next] == 0 && !visited[next] {
+                    ready.push(Reverse(ranks[next]));
+                }
+            }
+        }
+        order
+    }
+}
+
+/// Sorts the active mods of `config`, active mods that aren't installed
+/// are kept at the end in their previous order.
+pub fn sort_load_order(mods: &OrderedItems<Mod>, config: &ModsConfigData) -> Vec<String> {
+    let mut order = RuleGraph::new(mods, config).sort();
+    let missing: Vec<String> = config
+        .active_mods
+        .iter()
+        .filter(|id| !order.iter().any(|o| o.eq_ignore_ascii_case(id)))
+        .cloned()
+        .collect();
+    order.extend(missing);
+    order
+}
+
+/// Cycles between the installed mods that are active in `config`.
+pub fn find_cycles(mods: &OrderedItems<Mod>, config: &ModsConfigData) -> Vec<Cycle> {
+    RuleGraph::new(mods, config).cycles()
+}
+
+/// Kosaraju's algorithm, only components with more than one node are returned.
+///
+/// Nodes inside each component are sorted and components are ordered by
+/// their first node.
+fn strongly_connected(len: usize, rules: &[Rule]) -> Vec<Vec<usize>> {
+    let mut outgoing = vec![vec![]; len];
+    let mut incoming = vec![vec![]; len];
+    for rule in rules {
+        outgoing[rule.before].push(rule.after);
+        incoming[rule.after].push(rule.before);
+    }
+
+    let mut visited = vec![false; len];
+    let mut finished = Vec::with_capacity(len);
+    for root in 0..len {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, edge)) = stack.pop() {
+            match outgoing[node].get(edge) {
+                Some(&next) => {
+                    stack.push((node, edge + 1));
+                    if !visited[next] {
+                        visited[next] = true;
+                        stack.push((next, 0));
+                    }
+                }
+                None => finished.push(node),
+            }
+        }
+    }
+
+    let mut component_of = vec![None; len];
+    let mut components = vec![];
+    for &root in finished.iter().rev() {
+        if component_of[root].is_some() {
+            continue;
+        }
+        let id = components.len();
+        component_of[root] = Some(id);
+        let mut component = vec![];
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            component.push(node);
+            for &prev in &incoming[node] {
+                if component_of[prev].is_none() {
+                    component_of[prev] = Some(id);
+                    stack.push(prev);
+                }
+            }
+        }
+        components.push(component);
+    }
+
+    let mut components: Vec<Vec<usize>> = components
+        .into_iter()
+        .filter(|c| c.len() > 1)
+        .map(|mut c| {
+            c.sort_unstable();
+            c
+        })
+        .collect();
+    components.sort_unstable_by_key(|c| c[0]);
+    components
+}
+
+fn is_official(package_id: &str) -> bool {
+    package_id
+        .get(.."ludeon.rimworld".len())
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("ludeon.rimworld"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::test_util::{config, game_mod};
+
+    #[test]
+    fn load_after_and_load_before_are_respected() {
+        let mods: OrderedItems<Mod> = vec![
+            game_mod("A", "a.a", "<loadAfter><li>C.C</li></loadAfter>"),
+            game_mod("B", "b.b", "<loadBefore><li>a.a</li></loadBefore>"),
+            game_mod("C", "c.c", ""),
+        ]
+        .into();
+        let order = sort_load_order(&mods, &config(&["a.a", "b.b", "c.c"]));
+        assert_eq!(order, ["b.b", "c.c", "a.a"]);
+    }
+
+    #[test]
+    fn official_content_and_its_requirements_go_first() {
+        let mods: OrderedItems<Mod> = vec![
+            game_mod("Aaa", "someone.aaa", ""),
+            game_mod("Core", "Ludeon.RimWorld", ""),
+            game_mod(
+                "Harmony",
+                "brrainz.harmony",
+                "<loadBefore><li>ludeon.rimworld</li></loadBefore>",
+            ),
+            game_mod("Royalty", "ludeon.rimworld.royalty", ""),
+        ]
+        .into();
+        let active = [
+            "someone.aaa",
+            "ludeon.rimworld.royalty",
+            "ludeon.rimworld",
+            "brrainz.harmony",
+        ];
+        let order = sort_load_order(&mods, &config(&active));
+        assert_eq!(
+            order,
+            [
+                "brrainz.harmony",
+                "ludeon.rimworld",
+                "ludeon.rimworld.royalty",
+                "someone.aaa"
+            ]
+        );
+    }
+
+    #[test]
+    fn unconstrained_mods_keep_the_mod_order() {
+        let mods: OrderedItems<Mod> = vec![
+            game_mod(
+                "Alpha",
+                "z.alpha",
+                "<modDependencies><li><packageId>x.gamma</packageId></li></modDependencies>",
+            ),
+            game_mod("Beta", "y.beta", ""),
+            game_mod("Gamma", "x.gamma", ""),
+            game_mod("Delta", "w.delta", ""),
+        ]
+        .into();
+        let order = sort_load_order(&mods, &config(&["x.gamma", "w.delta", "y.beta", "z.alpha"]));
+        assert_eq!(order, ["y.beta", "w.delta", "x.gamma", "z.alpha"]);
+    }
+
+    #[test]
+    fn missing_mods_are_kept_at_the_end() {
+        let mods: OrderedItems<Mod> = vec![
+            game_mod(
+                "Alpha",
+                "a.alpha",
+                "<modDependencies><li><packageId>not.installed</packageId></li></modDependencies>\
+                 <loadAfter><li>b.inactive</li></loadAfter>",
+            ),
+            game_mod("Inactive", "b.inactive", ""),
+        ]
+        .into();
+        let order = sort_load_order(&mods, &config(&["gone.first", "a.alpha", "gone.second"]));
+        assert_eq!(order, ["a.alpha", "gone.first", "gone.second"]);
+    }
+
+    #[test]
+    fn uninstalled_mods_have_no_say() {
+        let mut gone = game_mod(
+            "Gone",
+            "x.gone",
+            "<loadAfter><li>a.a</li></loadAfter><loadBefore><li>b.b</li></loadBefore>",
+        );
+        gone.missing = true;
+        let mods: OrderedItems<Mod> = vec![
+            game_mod("A", "a.a", "<loadAfter><li>b.b</li></loadAfter>"),
+            game_mod("B", "b.b", ""),
+            gone,
+        ]
+        .into();
+        let config = config(&["a.a", "x.gone", "b.b"]);
+        assert!(find_cycles(&mods, &config).is_empty());
+        assert_eq!(sort_load_order(&mods, &config), ["b.b", "a.a", "x.gone"]);
+    }
+
+    fn rules(cycle: &Cycle) -> Vec<String> {
+        cycle.rules.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn contradicting_mod_is_a_cycle() {
+        let mods: OrderedItems<Mod> = vec![
+            game_mod(
+                "A",
+                "a.a",
+                "<loadAfter><li>b.b</li></loadAfter><loadBefore><li>b.b</li></loadBefore>",
+            ),
+            game_mod("B", "b.b", ""),
+            game_mod("C", "c.c", ""),
+        ]
+        .into();
+        let config = config(&["c.c", "b.b", "a.a"]);
+        let cycles = find_cycles(&mods, &config);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].package_ids, ["a.a", "b.b"]);
+        assert_eq!(
+            rules(&cycles[0]),
+            ["a.a loadAfter b.b", "a.a loadBefore b.b"]
+        );
+
+        // both rules are as weak, neither is kept and the mod order decides
+        let order = sort_load_order(&mods, &config);
+        assert_eq!(order, ["a.a", "b.b", "c.c"]);
+        // the mod contradicts itself, sorting doesn't make the cycle go away
+        let sorted = ModsConfigData {
+            active_mods: order,
+            ..Default::default()
+        };
+        let cycles_after = find_cycles(&mods, &sorted);
+        assert_eq!(cycles_after.len(), 1);
+        assert_eq!(cycles_after[0].package_ids, ["a.a", "b.b"]);
+    }
+
+    #[test]
+    fn cycles_are_broken_by_their_weakest_rules() {
+        let mods: OrderedItems<Mod> = vec![
+            game_mod(
+                "A",
+                "a.a",
+                "<modDependencies><li><packageId>c.c</packageId></li></modDependencies>",
+            ),
+            game_mod("B", "b.b", "<loadAfter><li>a.a</li></loadAfter>"),
+            game_mod("C", "c.c", "<loadAfter><li>b.b</li></loadAfter>"),
+            game_mod("D", "d.d", ""),
+        ]
+        .into();
+        let config = config(&["a.a", "b.b", "c.c", "d.d"]);
+        let cycles = find_cycles(&mods, &config);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].package_ids, ["a.a", "b.b", "c.c"]);
+        assert_eq!(
+            rules(&cycles[0]),
+            [
+                "a.a modDependencies c.c",
+                "b.b loadAfter a.a",
+                "c.c loadAfter b.b"
+            ]
+        );
+
+        // both loadAfter rules go, the dependency stays
+        let order = sort_load_order(&mods, &config);
+        assert_eq!(order, ["b.b", "c.c", "a.a", "d.d"]);
+    }
+}
@@ -3,7 +3,7 @@ use std::hash::Hash;
 
 use ratatui::{
     style::{Color, Style, Stylize as _},
-    text::{Line, Span},
+    text::Span,
 };
 use serde::{Deserialize, Serialize};
 
@@ -60,26 +60,64 @@ impl Item for Tag {
     }
 }
 
-impl OrderedItems<Tag> {
-    pub fn spans(&self, bg_color: Color, selected_tag: SelectedTag) -> TagSpans {
-        TagSpans {
-            idx: 0,
-            selected_tag,
-            bg_color,
-            tags: self,
+/// A mod's reference to a [`Tag`] of the registry, only the name is saved.
+///
+/// `score` mirrors the registry's to keep the tags ordered, it is refreshed
+/// by `OrderedItems<Mod>::sync_tags`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub struct TagRef {
+    pub name: String,
+    pub(super) score: u64,
+}
+impl From<&Tag> for TagRef {
+    fn from(tag: &Tag) -> Self {
+        Self {
+            name: tag.name.clone(),
+            score: tag.score,
         }
     }
-    pub fn styled_line(&self, bg_color: Color, is_selected: bool) -> Line {
-        let mut buff = vec![];
-        let selected = if is_selected {
-            SelectedTag::All
-        } else {
-            SelectedTag::None
-        };
-        for span in TagSpans::new(selected, bg_color, self) {
-            buff.push(span);
-        }
-        Line::from(buff)
+}
+impl From<String> for TagRef {
+    fn from(name: String) -> Self {
+        Self { name, score: 0 }
+    }
+}
+impl From<TagRef> for String {
+    fn from(tag: TagRef) -> Self {
+        tag.name
+    }
+}
+impl Item for TagRef {
+    fn identifier(&self) -> &str {
+        &self.name
+    }
+
+    fn patch(&mut self, other: Self) {
+        self.score = other.score;
+    }
+
+    fn vec_order(&self, other: &Self) -> Ordering {
+        self.score
+            .cmp(&other.score)
+            .then(self.name.cmp(&other.name))
+    }
+}
+
+impl OrderedItems<Tag> {
+    pub fn spans(
+        &self,
+        bg_color: Color,
+        muted_color: Color,
+        selected_tag: SelectedTag,
+    ) -> TagSpans<'_> {
+        TagSpans::new(selected_tag, bg_color, muted_color, self.iter().collect())
+    }
+    /// The tags `refs` points to, dangling references are skipped.
+    pub fn resolve<'tags>(&'tags self, refs: &OrderedItems<TagRef>) -> Vec<&'tags Tag> {
+        refs.iter()
+            .filter_map(|tag| self.get_by_name(&tag.name))
+            .collect()
     }
 }
 
@@ -92,15 +130,23 @@ pub struct TagSpans<'tags> {
     idx: usize,
     selected_tag: SelectedTag,
     bg_color: Color,
-    tags: &'tags OrderedItems<Tag>,
+    /// Color of the placeholder shown when there are no tags.
+    muted_color: Color,
+    tags: Vec<&'tags Tag>,
 }
 
 impl<'tags> TagSpans<'tags> {
-    fn new(selected_tag: SelectedTag, bg_color: Color, tags: &'tags OrderedItems<Tag>) -> Self {
+    pub fn new(
+        selected_tag: SelectedTag,
+        bg_color: Color,
+        muted_color: Color,
+        tags: Vec<&'tags Tag>,
+    ) -> Self {
         Self {
             idx: 0,
             selected_tag,
             bg_color,
+            muted_color,
             tags,
         }
     }
@@ -116,12 +162,12 @@ impl<'spans> Iterator for TagSpans<'spans> {
                 self.idx = 1;
                 Some(Span::styled(
                     "N/A",
-                    Style::default().italic().fg(Color::Rgb(0x8d, 0x8d, 0x8d)),
+                    Style::default().italic().fg(self.muted_color),
                 ))
             };
         }
 
-        let tag = self.tags.get(self.idx)?;
+        let tag = *self.tags.get(self.idx)?;
         let bg = Tag::blend_color(tag.color, self.bg_color, 0.8);
         let (text_color, text_bg) = match self.selected_tag {
             SelectedTag::All => (bg, tag.color),
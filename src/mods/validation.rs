@@ -0,0 +1,210 @@
+//! Sanity checks over the active load order
+//!
+//! [`validate`] walks the active mods of [`ModsConfigData`] and reports an
+//! [`Issue`] for every dependency that isn't active or is loaded too late,
+//! and for every pair of incompatible mods that are both active.
+use std::{collections::HashMap, fmt};
+
+use super::{Item, Mod, OrderedItems, game::ModsConfigData};
+
+#[derive(Clone, Debug)]
+pub enum IssueKind {
+    MissingDependency {
+        package_id: String,
+        display_name: String,
+        /// The dependency is active but isn't installed.
+        active: bool,
+    },
+    DependencyLoadedAfter {
+        package_id: String,
+    },
+    Incompatible {
+        package_id: String,
+    },
+}
+/// A problem with the mod identified by `package_id`.
+#[derive(Clone, Debug)]
+pub struct Issue {
+    pub package_id: String,
+    pub kind: IssueKind,
+}
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            IssueKind::MissingDependency {
+                package_id,
+                display_name,
+                active,
+            } => write!(
+                f,
+                "{} depends on {display_name} ({package_id}) which is not {}",
+                self.package_id,
+                if *active { "installed" } else { "active" }
+            ),
+            IssueKind::DependencyLoadedAfter { package_id } => write!(
+                f,
+                "{} depends on {package_id} which is loaded after it",
+                self.package_id
+            ),
+            IssueKind::Incompatible { package_id } => {
+                write!(f, "{} is incompatible with {package_id}", self.package_id)
+            }
+        }
+    }
+}
+
+/// Issues of the active mods, in load order.
+///
+/// Active mods that aren't installed are skipped, dependencies that aren't
+/// installed are missing even if they're active.
+pub fn validate(mods: &OrderedItems<Mod>, config: &ModsConfigData) -> Vec<Issue> {
+    let installed: HashMap<String, &Mod> = mods
+        .iter()
+        .filter(|m| !m.missing)
+        .map(|m| (m.identifier().to_ascii_lowercase(), m))
+        .collect();
+    let game_version = config.game_version();
+
+    let mut issues = vec![];
+    for (position, id) in config.active_mods.iter().enumerate() {
+        let Some(game_mod) = installed.get(&id.to_ascii_lowercase()) else {
+            continue;
+        };
+        let metadata = &game_mod.metadata;
+        for dep in metadata.dependencies_for(game_version) {
+            let kind = match (
+                config.position(&dep.package_id),
+                installed.contains_key(&dep.package_id.to_ascii_lowercase()),
+            ) {
+                (dep_position, false) | (dep_position @ None, true) => {
+                    IssueKind::MissingDependency {
+                        package_id: dep.package_id.clone(),
+                        display_name: dep.display_name.clone(),
+                        active: dep_position.is_some(),
+                    }
+                }
+                (Some(dep_position), true) if dep_position > position => {
+                    IssueKind::DependencyLoadedAfter {
+                        package_id: dep.package_id.clone(),
+                    }
+                }
+                (Some(_), true) => continue,
+            };
+            issues.push(Issue {
+                package_id: metadata.package_id.clone(),
+                kind,
+            });
+        }
+        for other in &metadata.incompatible_with {
+            if config.is_active(other) {
+                issues.push(Issue {
+                    package_id: metadata.package_id.clone(),
+                    kind: IssueKind::Incompatible {
+                        package_id: other.clone(),
+                    },
+                });
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mods::test_util;
+
+    fn game_mod(package_id: &str, rules: &str) -> Mod {
+        test_util::game_mod(package_id, package_id, rules)
+    }
+    fn config(version: &str, active: &[&str]) -> ModsConfigData {
+        ModsConfigData {
+            version: version.to_owned(),
+            ..test_util::config(active)
+        }
+    }
+    fn dependency(package_id: &str) -> String {
+        format!(
+            "<li><packageId>{package_id}</packageId><displayName>{package_id} name</displayName></li>"
+        )
+    }
+    fn messages(mods: Vec<Mod>, config: &ModsConfigData) -> Vec<String> {
+        validate(&mods.into(), config)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn inactive_dependency_is_missing() {
+        let mods = vec![game_mod(
+            "a.a",
+            &format!("<modDependencies>{}</modDependencies>", dependency("b.b")),
+        )];
+        assert_eq!(
+            messages(mods, &config("", &["a.a"])),
+            ["a.a depends on b.b name (b.b) which is not active"]
+        );
+    }
+
+    #[test]
+    fn active_dependency_that_is_not_installed_is_missing() {
+        let rules = format!("<modDependencies>{}</modDependencies>", dependency("b.b"));
+        let mut gone = game_mod("b.b", "");
+        gone.missing = true;
+        let expected = ["a.a depends on b.b name (b.b) which is not installed"];
+        assert_eq!(
+            messages(
+                vec![game_mod("a.a", &rules), gone],
+                &config("", &["b.b", "a.a"])
+            ),
+            expected
+        );
+        assert_eq!(
+            messages(vec![game_mod("a.a", &rules)], &config("", &["b.b", "a.a"])),
+            expected
+        );
+    }
+
+    #[test]
+    fn dependency_must_load_first() {
+        let rules = format!("<modDependencies>{}</modDependencies>", dependency("B.B"));
+        let mods = || vec![game_mod("a.a", &rules), game_mod("b.b", "")];
+        assert_eq!(
+            messages(mods(), &config("", &["a.a", "b.b"])),
+            ["a.a depends on B.B which is loaded after it"]
+        );
+        assert!(messages(mods(), &config("", &["b.b", "a.a"])).is_empty());
+    }
+
+    #[test]
+    fn active_incompatible_mods_are_reported() {
+        let rules = "<incompatibleWith><li>b.b</li></incompatibleWith>";
+        let mods = || vec![game_mod("a.a", rules), game_mod("b.b", "")];
+        assert_eq!(
+            messages(mods(), &config("", &["a.a", "b.b"])),
+            ["a.a is incompatible with b.b"]
+        );
+        assert!(messages(mods(), &config("", &["a.a"])).is_empty());
+    }
+
+    #[test]
+    fn dependencies_of_the_game_version_are_checked() {
+        let rules = format!(
+            "<modDependenciesByVersion><v1.4>{}</v1.4><v1.5>{}</v1.5></modDependenciesByVersion>",
+            dependency("old.dep"),
+            dependency("new.dep"),
+        );
+        let mods = || vec![game_mod("a.a", &rules)];
+        assert_eq!(
+            messages(mods(), &config("1.5.4104 rev435", &["a.a"])),
+            ["a.a depends on new.dep name (new.dep) which is not active"]
+        );
+        assert_eq!(
+            messages(mods(), &config("1.4.3901 rev1", &["a.a"])),
+            ["a.a depends on old.dep name (old.dep) which is not active"]
+        );
+        // inactive mods aren't checked
+        assert!(messages(mods(), &config("1.5.4104 rev435", &[])).is_empty());
+    }
+}